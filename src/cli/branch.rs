@@ -0,0 +1,69 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::branch::CreateBranch;
+use crate::commands::common::Command;
+use crate::config::data::user_config::UserConfig;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("branch")
+        .about("Create a branch in each project, then print status for each project")
+        .after_help("The branch is created from the checked out commit, or with --from-default from the default branch of the main remote: the default_branch setting in the projects file if set, otherwise the branch that the remote's HEAD points to. The new branch is not checked out; use `gws switch` for that.
+
+Projects where the branch already exists are left alone.
+
+If no <path>s are given, create the branch in all projects.")
+        .arg(
+            Arg::with_name("name")
+                .required(true)
+                .help("Name of the branch to create"),
+        )
+        .arg(super::format_arg())
+        .arg(
+            Arg::with_name("from-default")
+                .long("from-default")
+                .help("Start from the default branch of the main remote instead of the checked out commit"),
+        )
+        .arg(
+            Arg::with_name("set-upstream")
+                .short("u")
+                .long("set-upstream")
+                .takes_value(true)
+                .value_name("REMOTE")
+                .help("Make the new branch track the branch with the same name on <REMOTE>, if it exists"),
+        )
+        .arg(super::group_arg())
+        .arg(super::here_arg())
+        .arg(super::no_here_arg())
+        .arg(super::exclude_group_arg())
+        .arg(Arg::with_name("path").multiple(true).help(
+            "Project paths or glob patterns to create the branch in, relative to the current directory",
+        ))
+}
+
+pub fn make_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> CreateBranch {
+    CreateBranch {
+        status_command: super::status::make_command(matches, config, ctx),
+        name: matches
+            .value_of("name")
+            .expect("name argument required")
+            .to_string(),
+        from_default: matches.is_present("from-default"),
+        upstream_remote: matches.value_of("set-upstream").map(str::to_string),
+    }
+}
+
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::RepositoryCommand(Box::new(make_command(matches, config, ctx)))
+}
@@ -0,0 +1,24 @@
+use clap::App;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use crate::commands::check::Check;
+use crate::commands::common::Command;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("check")
+        .about("Check the workspace for known, unknown, ignored and missing repositories")
+        .after_help(
+            "Exits with a nonzero status if any repository is missing, or is neither in the projects list nor matched by .ignore.gws.
+
+This searches the entire workspace for git repositories, so it can be slow in large workspaces.",
+        )
+}
+
+pub fn make_command(_matches: &ArgMatches) -> Check {
+    Check {}
+}
+
+pub fn make_cli_command(matches: &ArgMatches) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches)))
+}
@@ -3,28 +3,36 @@ use clap::Arg;
 use clap::ArgMatches;
 use clap::SubCommand;
 
+use super::WorkspaceContext;
 use crate::commands::clone::Clone;
 use crate::commands::common::Command;
+use crate::config::data::user_config::UserConfig;
 
 pub fn subcommand_def<'a>() -> App<'a, 'a> {
     SubCommand::with_name("clone")
         .about("Selectively clone specific repositories from projects list")
+        .arg(super::jobs_arg())
+        .arg(super::group_arg())
+        .arg(super::exclude_group_arg())
         .arg(
-            Arg::with_name("path")
-                .multiple(true)
-                .help("Project paths to be cloned"),
+            Arg::with_name("path").multiple(true).help(
+                "Project paths or glob patterns to be cloned, relative to the current directory",
+            ),
         )
 }
 
-pub fn make_command(matches: &ArgMatches) -> Clone {
+pub fn make_command(matches: &ArgMatches, config: &UserConfig, ctx: &WorkspaceContext) -> Clone {
     Clone {
-        projects: matches
-            .values_of("path")
-            .map(|values| values.map(&str::to_string).collect())
-            .unwrap_or_default(),
+        projects: super::project_filter(matches, ctx, super::Here::Off),
+        jobs: super::jobs_value(matches, config),
+        credentials: super::credentials_value(config),
     }
 }
 
-pub fn make_cli_command(matches: &ArgMatches) -> Command {
-    Command::DirectoryCommand(Box::new(make_command(matches)))
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, config, ctx)))
 }
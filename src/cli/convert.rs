@@ -0,0 +1,22 @@
+use clap::App;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use crate::commands::common::Command;
+use crate::commands::convert::Convert;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("convert")
+        .about("Convert the .projects.gws file to the .projects.toml format")
+        .after_help(
+            "The .projects.toml file is written next to .projects.gws, which is left in place. When both files exist, .projects.toml is used. Comments in .projects.gws are not carried over.",
+        )
+}
+
+pub fn make_command(_matches: &ArgMatches) -> Convert {
+    Convert {}
+}
+
+pub fn make_cli_command(matches: &ArgMatches) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches)))
+}
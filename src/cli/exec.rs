@@ -0,0 +1,60 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::exec::Exec;
+use crate::config::data::user_config::UserConfig;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("exec")
+        .about("Run a command in each project")
+        .after_help("The command is run with each project directory as its working directory. Its output is printed under a header for each project, in workspace order, also when running in parallel.
+
+The exit code is 0 if the command succeeded in all projects, 1 if it failed in any, and 3 if it could not be started.
+
+If no <path>s are given, run the command in all projects.")
+        .arg(super::jobs_arg())
+        .arg(
+            Arg::with_name("fail-fast")
+                .long("fail-fast")
+                .help("Don't run the command in any more projects after it fails in one"),
+        )
+        .arg(super::group_arg())
+        .arg(super::here_arg())
+        .arg(super::no_here_arg())
+        .arg(super::exclude_group_arg())
+        .arg(Arg::with_name("path").multiple(true).help(
+            "Project paths or glob patterns to run the command in, relative to the current directory",
+        ))
+        .arg(
+            Arg::with_name("command")
+                .last(true)
+                .multiple(true)
+                .required(true)
+                .help("The command to run, after --"),
+        )
+}
+
+pub fn make_command(matches: &ArgMatches, config: &UserConfig, ctx: &WorkspaceContext) -> Exec {
+    Exec {
+        projects: super::project_filter(matches, ctx, super::here_value(matches, config)),
+        jobs: super::jobs_value(matches, config),
+        fail_fast: matches.is_present("fail-fast"),
+        command: matches
+            .values_of("command")
+            .expect("command argument required")
+            .map(&str::to_string)
+            .collect(),
+    }
+}
+
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, config, ctx)))
+}
@@ -3,31 +3,91 @@ use clap::Arg;
 use clap::ArgMatches;
 use clap::SubCommand;
 
+use super::WorkspaceContext;
 use crate::commands::common::Command;
 use crate::commands::fetch::Fetch;
+use crate::config::data::user_config::UserConfig;
 
 pub fn subcommand_def<'a>() -> App<'a, 'a> {
     SubCommand::with_name("fetch")
         .about("Print status for each project, but fetch remotes first")
-        .after_help("If no <path>s are given, fetch all projects.")
+        .after_help("Without --prune or --no-prune, remote-tracking branches are pruned according to git's remote.<name>.prune and fetch.prune settings. Pruned branches are listed under each project.
+
+If no <path>s are given, fetch all projects.")
+        .arg(super::jobs_arg())
+        .arg(super::format_arg())
         .arg(
             Arg::with_name("only-changes")
                 .long("only-changes")
                 .help("Only print out-of-sync repositories and branches"),
         )
         .arg(
-            Arg::with_name("path")
-                .multiple(true)
-                .help("Project paths to be fetched"),
+            Arg::with_name("prune")
+                .long("prune")
+                .overrides_with("no-prune")
+                .help("Remove remote-tracking branches that no longer exist on the remote"),
+        )
+        .arg(
+            Arg::with_name("no-prune")
+                .long("no-prune")
+                .overrides_with("prune")
+                .help("Don't remove remote-tracking branches, whatever the git config says"),
+        )
+        .arg(
+            Arg::with_name("tags")
+                .long("tags")
+                .overrides_with("no-tags")
+                .help("Fetch all tags from the remotes"),
+        )
+        .arg(
+            Arg::with_name("no-tags")
+                .long("no-tags")
+                .overrides_with("tags")
+                .help("Don't fetch any tags"),
+        )
+        .arg(
+            Arg::with_name("remote")
+                .long("remote")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Only fetch the remote NAME, in the projects that have it. It is an error if none has it"),
         )
+        .arg(super::group_arg())
+        .arg(super::here_arg())
+        .arg(super::no_here_arg())
+        .arg(super::exclude_group_arg())
+        .arg(Arg::with_name("path").multiple(true).help(
+            "Project paths or glob patterns to be fetched, relative to the current directory",
+        ))
+}
+
+/// `Some(true)` if the `on` flag was given, `Some(false)` if the `off` flag was
+/// given, and `None` if neither was.
+fn flag_value(matches: &ArgMatches, on: &str, off: &str) -> Option<bool> {
+    if matches.is_present(on) {
+        Some(true)
+    } else if matches.is_present(off) {
+        Some(false)
+    } else {
+        None
+    }
 }
 
-pub fn make_command(matches: &ArgMatches) -> Fetch {
+pub fn make_command(matches: &ArgMatches, config: &UserConfig, ctx: &WorkspaceContext) -> Fetch {
     Fetch {
-        status_command: super::status::make_command(matches),
+        status_command: super::status::make_command(matches, config, ctx),
+        jobs: super::jobs_value(matches, config),
+        credentials: super::credentials_value(config),
+        prune: flag_value(matches, "prune", "no-prune"),
+        tags: flag_value(matches, "tags", "no-tags"),
+        remote: matches.value_of("remote").map(str::to_string),
     }
 }
 
-pub fn make_cli_command(matches: &ArgMatches) -> Command {
-    Command::RepositoryCommand(Box::new(make_command(matches)))
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::RepositoryCommand(Box::new(make_command(matches, config, ctx)))
 }
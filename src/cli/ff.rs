@@ -3,33 +3,57 @@ use clap::Arg;
 use clap::ArgMatches;
 use clap::SubCommand;
 
+use super::WorkspaceContext;
 use crate::commands::common::Command;
 use crate::commands::ff::FastForward;
+use crate::config::data::user_config::UserConfig;
 
 pub fn subcommand_def<'a>() -> App<'a, 'a> {
     SubCommand::with_name("ff")
         .about("Print status for each project, but fetch remotes and fast-forward branches first")
-    .after_help("Branches are fast-forwarded to their upstreams as configured in git, outside the workspace configuration file.
+    .after_help("Branches are fast-forwarded to their upstreams as configured in git, outside the workspace configuration file. The working tree of a checked-out branch is only updated if that does not overwrite local changes.
+
+With --rebase, clean branches that have diverged from their upstreams are rebased onto them. If the rebase has conflicts it is aborted and the branch is left unchanged.
 
 If no <path>s are given, fast-forward all projects.")
+        .arg(super::jobs_arg())
+        .arg(super::format_arg())
         .arg(
             Arg::with_name("only-changes")
                 .long("only-changes")
                 .help("Only print out-of-sync or fast-forwarded repositories and branches"),
         )
+        .arg(
+            Arg::with_name("rebase")
+                .long("rebase")
+                .help("Rebase clean branches that have diverged from their upstreams"),
+        )
+        .arg(super::group_arg())
+        .arg(super::here_arg())
+        .arg(super::no_here_arg())
+        .arg(super::exclude_group_arg())
         .arg(
             Arg::with_name("path")
                 .multiple(true)
-                .help("Project paths to be fast-forwarded"),
+                .help("Project paths or glob patterns to be fast-forwarded, relative to the current directory"),
         )
 }
 
-pub fn make_command(matches: &ArgMatches) -> FastForward {
+pub fn make_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> FastForward {
     FastForward {
-        fetch_command: super::fetch::make_command(matches),
+        fetch_command: super::fetch::make_command(matches, config, ctx),
+        rebase: matches.is_present("rebase"),
     }
 }
 
-pub fn make_cli_command(matches: &ArgMatches) -> Command {
-    Command::RepositoryCommand(Box::new(make_command(matches)))
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::RepositoryCommand(Box::new(make_command(matches, config, ctx)))
 }
@@ -0,0 +1,30 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::freeze::Freeze;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("freeze")
+        .about("Record the checked out branch and commit of each project in a lockfile")
+        .after_help("Uncommitted changes are not recorded. Use `gws restore` to check out the recorded commits again.")
+        .arg(Arg::with_name("lockfile").help(
+            "File to write, relative to the current directory [default: .projects.lock next to the projects file]",
+        ))
+}
+
+pub fn make_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Freeze {
+    Freeze {
+        lockfile: matches
+            .value_of("lockfile")
+            .map(|file| ctx.working_dir.join(file))
+            .unwrap_or_else(|| ctx.workspace_dir.join(".projects.lock")),
+    }
+}
+
+pub fn make_cli_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, ctx)))
+}
@@ -0,0 +1,19 @@
+use clap::App;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use crate::commands::init::Init;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("init")
+        .about("Create a projects list from the repositories already in the current directory")
+        .after_help(
+            "The main remote of each project is \"origin\" if it exists. Repositories without remotes are skipped.
+
+Fails if a .projects.gws or .projects.toml file already exists in the current directory.",
+        )
+}
+
+pub fn make_command(_matches: &ArgMatches) -> Init {
+    Init {}
+}
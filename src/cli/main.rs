@@ -7,9 +7,11 @@ use std::path::PathBuf;
 use crate::color::palette::Palette;
 use crate::commands::common::exit_codes;
 use crate::commands::common::Command;
+use crate::commands::error::Error;
 use crate::config::data::user_config::UserConfig;
 use crate::config::error::ConfigError;
 use crate::config::read::read_config_file;
+use crate::config::read::read_ignore_file;
 use crate::config::read::read_workspace_file;
 
 struct RunError {
@@ -67,12 +69,12 @@ fn run_completions(matches: ArgMatches) -> i32 {
 }
 
 fn find_workspace(current_dir: &Path) -> Option<(&Path, PathBuf)> {
-    let ws_file_path = current_dir.join(".projects.gws");
-    if ws_file_path.exists() {
-        Some((current_dir, ws_file_path))
-    } else {
-        current_dir.parent().and_then(find_workspace)
-    }
+    [".projects.toml", ".projects.gws"]
+        .iter()
+        .map(|file_name| current_dir.join(file_name))
+        .find(|ws_file_path| ws_file_path.exists())
+        .map(|ws_file_path| (current_dir, ws_file_path))
+        .or_else(|| current_dir.parent().and_then(find_workspace))
 }
 
 fn find_config_file(matches: &ArgMatches) -> Option<PathBuf> {
@@ -86,53 +88,122 @@ fn find_config_file(matches: &ArgMatches) -> Option<PathBuf> {
 }
 
 fn run_gws(matches: ArgMatches) -> Result<i32, RunError> {
-    let config: Option<UserConfig> = match find_config_file(&matches) {
-        Some(config_path) => Some(read_config_file(&config_path)?),
-        None => None,
+    let config: UserConfig = match find_config_file(&matches) {
+        Some(config_path) => read_config_file(&config_path)?,
+        None => UserConfig::default(),
     };
 
-    let palette = match config {
-        Some(conf) => conf.palette()?,
-        None => None,
-    }
-    .unwrap_or_else(Palette::default);
-
-    let subcommand: Command = match &matches.subcommand {
-        None => super::status::make_cli_command(&matches),
-        Some(sc) => match sc.name.as_ref() {
-            "clone" => super::clone::make_cli_command(&sc.matches),
-            "fetch" => super::fetch::make_cli_command(&sc.matches),
-            "ff" => super::ff::make_cli_command(&sc.matches),
-            "status" => super::status::make_cli_command(&sc.matches),
-            "update" => super::update::make_cli_command(&sc.matches),
-            _ => unreachable!(),
-        },
-    };
+    let palette = config.palette()?.unwrap_or_else(Palette::default);
+
+    let current_dir: PathBuf = std::env::current_dir().map_err(|e| {
+        RunError::from(
+            exit_codes::INTERNAL_ERROR,
+            format!("Failed to read current directory: {}", e),
+        )
+    })?;
 
-    let working_dir: &Path = match matches.args.get("dir") {
-        Some(chdir_arg) => Path::new(chdir_arg.vals[0].to_str().ok_or_else(|| {
+    // Make the working directory absolute so that find_workspace can walk up
+    // from it, and so that path arguments can be resolved relative to it.
+    let working_dir: PathBuf = match matches.args.get("dir") {
+        Some(chdir_arg) => current_dir.join(chdir_arg.vals[0].to_str().ok_or_else(|| {
             RunError::from(
                 exit_codes::USER_ERROR,
                 "Did not understand <dir> argument".to_string(),
             )
         })?),
-        None => Path::new("."),
+        None => current_dir,
+    };
+    let working_dir: PathBuf = std::fs::canonicalize(&working_dir).unwrap_or(working_dir);
+    let working_dir: &Path = &working_dir;
+
+    if let Some(sc) = &matches.subcommand {
+        if sc.name == "init" {
+            return super::init::make_command(&sc.matches)
+                .run(working_dir, &palette)
+                .map_err(|e| match e {
+                    Error::Io(err) => RunError::from(
+                        exit_codes::USER_ERROR,
+                        format!(
+                            "Failed to initialize workspace in {:?}: {}",
+                            working_dir, err
+                        ),
+                    ),
+                    err => RunError::from(
+                        exit_codes::INTERNAL_ERROR,
+                        format!("Failed to initialize workspace: {}", err),
+                    ),
+                });
+        }
+    }
+
+    // Without a subcommand, gws runs status with the top-level options
+    let format = match &matches.subcommand {
+        Some(sc) => super::serialized_format(&sc.matches),
+        None => super::serialized_format(&matches),
     };
 
     match find_workspace(working_dir) {
         Some((workspace_dir, ws_file_path)) => match read_workspace_file(&ws_file_path) {
             Ok(ws) => {
+                let ignore_file_path = workspace_dir.join(".ignore.gws");
+                let ws = if ignore_file_path.exists() {
+                    ws.ignoring(read_ignore_file(&ignore_file_path).map_err(|e| {
+                        RunError::from(
+                            exit_codes::USER_ERROR,
+                            format!(
+                                "Failed to parse ignore file {:?}: {:?}",
+                                ignore_file_path, e
+                            ),
+                        )
+                    })?)
+                } else {
+                    ws
+                };
+
+                let ctx = super::WorkspaceContext {
+                    working_dir,
+                    workspace_dir,
+                    workspace: &ws,
+                };
+                let subcommand: Command = match &matches.subcommand {
+                    None => super::status::make_cli_command(&matches, &config, &ctx),
+                    Some(sc) => match sc.name.as_ref() {
+                        "branch" => super::branch::make_cli_command(&sc.matches, &config, &ctx),
+                        "check" => super::check::make_cli_command(&sc.matches),
+                        "clone" => super::clone::make_cli_command(&sc.matches, &config, &ctx),
+                        "convert" => super::convert::make_cli_command(&sc.matches),
+                        "exec" => super::exec::make_cli_command(&sc.matches, &config, &ctx),
+                        "fetch" => super::fetch::make_cli_command(&sc.matches, &config, &ctx),
+                        "ff" => super::ff::make_cli_command(&sc.matches, &config, &ctx),
+                        "freeze" => super::freeze::make_cli_command(&sc.matches, &ctx),
+                        "prune" => super::prune::make_cli_command(&sc.matches, &ctx),
+                        "prune-branches" => {
+                            super::prune_branches::make_cli_command(&sc.matches, &ctx)
+                        }
+                        "push" => super::push::make_cli_command(&sc.matches, &config, &ctx),
+                        "remotes" => super::remotes::make_cli_command(&sc.matches, &ctx),
+                        "restore" => super::restore::make_cli_command(&sc.matches, &ctx),
+                        "status" => super::status::make_cli_command(&sc.matches, &config, &ctx),
+                        "switch" => super::switch::make_cli_command(&sc.matches, &config, &ctx),
+                        "update" => super::update::make_cli_command(&sc.matches, &config, &ctx),
+                        _ => unreachable!(),
+                    },
+                };
+
                 let result = match subcommand {
                     Command::DirectoryCommand(cmd) => cmd.run(workspace_dir, &ws, &palette),
-                    Command::RepositoryCommand(cmd) => cmd.run(workspace_dir, &ws, &palette),
+                    Command::RepositoryCommand(cmd) => match format {
+                        Some(format) => cmd.run_serialized(workspace_dir, &ws, format),
+                        None => cmd.run(workspace_dir, &ws, &palette),
+                    },
                 };
                 result.map_err(|_| {
                     RunError::from(exit_codes::UNKNOWN_ERROR, "Unknown error".to_string())
                 })
             }
-            Err(_) => Err(RunError::from(
+            Err(err) => Err(RunError::from(
                 exit_codes::USER_ERROR,
-                format!("Failed to parse projects file: {:?}", ws_file_path),
+                format!("Failed to parse projects file {:?}: {}", ws_file_path, err),
             )),
         },
         None => Err(RunError::from(
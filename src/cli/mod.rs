@@ -1,21 +1,41 @@
-// pub mod check;
+pub mod branch;
+pub mod check;
 pub mod clone;
+pub mod convert;
+pub mod exec;
 pub mod fetch;
 pub mod ff;
-// pub mod init;
+pub mod freeze;
+pub mod init;
 pub mod main;
+pub mod prune;
+pub mod prune_branches;
+pub mod push;
+pub mod remotes;
+pub mod restore;
 mod status;
+pub mod switch;
 pub mod update;
 
+use std::collections::HashSet;
+use std::path::Path;
+
 use clap::App;
 use clap::Arg;
+use clap::ArgMatches;
 use clap::Shell;
 use clap::SubCommand;
 
+use crate::commands::common::credentials::Credentials;
+use crate::commands::common::filter::PathPattern;
+use crate::commands::common::filter::ProjectFilter;
+use crate::config::data::user_config::UserConfig;
+use crate::config::data::Workspace;
 use crate::crate_info::crate_author;
 use crate::crate_info::crate_description;
 use crate::crate_info::crate_name;
 use crate::crate_info::crate_version;
+use crate::data::serialize::SerializedFormat;
 
 pub fn build_cli() -> App<'static, 'static> {
     App::new(crate_name())
@@ -34,11 +54,24 @@ pub fn build_cli() -> App<'static, 'static> {
                 .long("no-config")
                 .help("Don't read any config files"),
         )
+        .arg(format_arg())
+        .subcommand(branch::subcommand_def())
+        .subcommand(check::subcommand_def())
         .subcommand(clone::subcommand_def())
         .subcommand(completions())
+        .subcommand(convert::subcommand_def())
+        .subcommand(exec::subcommand_def())
         .subcommand(fetch::subcommand_def())
         .subcommand(ff::subcommand_def())
+        .subcommand(freeze::subcommand_def())
+        .subcommand(init::subcommand_def())
+        .subcommand(prune::subcommand_def())
+        .subcommand(prune_branches::subcommand_def())
+        .subcommand(push::subcommand_def())
+        .subcommand(remotes::subcommand_def())
+        .subcommand(restore::subcommand_def())
         .subcommand(status::subcommand_def())
+        .subcommand(switch::subcommand_def())
         .subcommand(update::subcommand_def())
 }
 
@@ -52,3 +85,156 @@ fn completions<'a>() -> App<'a, 'a> {
                 .possible_values(&Shell::variants()),
         )
 }
+
+fn jobs_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("jobs")
+        .short("j")
+        .long("jobs")
+        .takes_value(true)
+        .value_name("N")
+        .validator(|v| match v.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(()),
+            _ => Err("must be a positive integer".to_string()),
+        })
+        .help("Process up to N projects concurrently [default: the jobs setting in config.toml, or 1]")
+}
+
+fn jobs_value(matches: &ArgMatches, config: &UserConfig) -> usize {
+    matches
+        .value_of("jobs")
+        .and_then(|v| v.parse().ok())
+        .or_else(|| config.jobs())
+        .unwrap_or(1)
+}
+
+fn credentials_value(config: &UserConfig) -> Credentials {
+    Credentials {
+        ssh_keys: config.ssh_keys(),
+    }
+}
+
+fn group_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("group")
+        .short("g")
+        .long("group")
+        .takes_value(true)
+        .value_name("GROUP")
+        .multiple(true)
+        .number_of_values(1)
+        .help("Only include projects in GROUP. May be given more than once.")
+}
+
+fn exclude_group_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("exclude-group")
+        .long("exclude-group")
+        .takes_value(true)
+        .value_name("GROUP")
+        .multiple(true)
+        .number_of_values(1)
+        .help("Exclude projects in GROUP. May be given more than once.")
+}
+
+fn values_set(matches: &ArgMatches, name: &str) -> HashSet<String> {
+    matches
+        .values_of(name)
+        .map(|values| values.map(&str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn here_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("here")
+        .long("here")
+        .conflicts_with("path")
+        .overrides_with("no-here")
+        .help("Only include the project containing the current directory [default: the here setting in config.toml, or off]")
+}
+
+fn no_here_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("no-here")
+        .long("no-here")
+        .overrides_with("here")
+        .help("Include all projects, even if the here setting in config.toml is on")
+}
+
+/// Whether to restrict a command to the project containing the current
+/// directory, see `here_value`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Here {
+    Off,
+    /// From the `here` setting in the config file. All projects are included
+    /// if the current directory is not inside a project.
+    Default,
+    /// From `--here`. It is an error if the current directory is not inside a
+    /// project.
+    Explicit,
+}
+
+/// Whether to restrict the command to the project containing the current
+/// directory. Explicit path arguments override the config default.
+fn here_value(matches: &ArgMatches, config: &UserConfig) -> Here {
+    if matches.is_present("here") {
+        Here::Explicit
+    } else if matches.is_present("no-here") || matches.is_present("path") || !config.here() {
+        Here::Off
+    } else {
+        Here::Default
+    }
+}
+
+/// The workspace and the directories that path arguments are resolved against.
+pub struct WorkspaceContext<'a> {
+    /// The directory gws was invoked in.
+    pub working_dir: &'a Path,
+    /// The directory containing the projects file.
+    pub workspace_dir: &'a Path,
+    pub workspace: &'a Workspace,
+}
+
+/// `here` restricts the filter to the project containing the working
+/// directory, see `here_value`.
+fn project_filter(matches: &ArgMatches, ctx: &WorkspaceContext, here: Here) -> ProjectFilter {
+    let enclosing =
+        PathPattern::enclosing_project(ctx.working_dir, ctx.workspace_dir, &ctx.workspace.projects);
+    let in_project = ctx
+        .workspace
+        .projects
+        .iter()
+        .any(|p| enclosing.matches(&p.path));
+
+    let paths: Vec<PathPattern> = match here {
+        Here::Explicit => vec![enclosing],
+        Here::Default if in_project => vec![enclosing],
+        Here::Default => Vec::new(),
+        Here::Off => matches
+            .values_of("path")
+            .map(|values| {
+                values
+                    .map(|arg| PathPattern::new(arg, ctx.working_dir, ctx.workspace_dir))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    ProjectFilter {
+        paths,
+        groups: values_set(matches, "group"),
+        exclude_groups: values_set(matches, "exclude-group"),
+    }
+}
+
+fn format_arg<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["text", "json", "ndjson"])
+        .default_value("text")
+        .help("Output format. \"ndjson\" prints one JSON object per project per line.")
+}
+
+fn serialized_format(matches: &ArgMatches) -> Option<SerializedFormat> {
+    match matches.value_of("format") {
+        Some("json") => Some(SerializedFormat::Json),
+        Some("ndjson") => Some(SerializedFormat::Ndjson),
+        _ => None,
+    }
+}
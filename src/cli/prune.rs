@@ -0,0 +1,42 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::prune::Prune;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("prune")
+        .about("Delete or archive repositories that are not in the projects list")
+        .after_help(
+            "Repositories with uncommitted changes, stashes, an operation in progress, or commits not pushed to any remote are never pruned. Repositories listed in .ignore.gws are left alone. Deleting a repository also deletes its files ignored by git, like local configuration and build output, which are listed before asking for confirmation. The archive directory must be outside the workspace.",
+        )
+        .arg(
+            Arg::with_name("archive")
+                .long("archive")
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Move repositories into <DIR> instead of deleting them"),
+        )
+        .arg(
+            Arg::with_name("yes")
+                .short("y")
+                .long("yes")
+                .help("Don't ask for confirmation"),
+        )
+}
+
+pub fn make_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Prune {
+    Prune {
+        archive_dir: matches
+            .value_of("archive")
+            .map(|dir| ctx.working_dir.join(dir)),
+        assume_yes: matches.is_present("yes"),
+    }
+}
+
+pub fn make_cli_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, ctx)))
+}
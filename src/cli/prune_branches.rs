@@ -0,0 +1,38 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::prune_branches::PruneBranches;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("prune-branches")
+        .about("Delete local branches whose upstream is gone")
+        .after_help("A branch's upstream is gone when it is configured in git but the remote-tracking branch no longer exists, for example after fetching with --prune. Such branches are only deleted if they are fully merged into their former upstream or into the default branch of the main remote: the default_branch setting in the projects file if set, otherwise the branch that the remote's HEAD points to. The former upstream is only known if gws fetch pruned it. The checked out branch is never deleted.
+
+If no <path>s are given, prune branches in all projects.")
+        .arg(
+            Arg::with_name("dry-run")
+                .short("n")
+                .long("dry-run")
+                .help("Only print which branches would be deleted"),
+        )
+        .arg(super::group_arg())
+        .arg(super::exclude_group_arg())
+        .arg(Arg::with_name("path").multiple(true).help(
+            "Project paths or glob patterns to prune branches in, relative to the current directory",
+        ))
+}
+
+pub fn make_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> PruneBranches {
+    PruneBranches {
+        projects: super::project_filter(matches, ctx, super::Here::Off),
+        dry_run: matches.is_present("dry-run"),
+    }
+}
+
+pub fn make_cli_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, ctx)))
+}
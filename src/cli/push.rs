@@ -0,0 +1,60 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::push::Push;
+use crate::config::data::user_config::UserConfig;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("push")
+        .about("Push branches that are ahead of their upstreams, then print status for each project")
+        .after_help("Only branches that are strictly ahead of their upstreams, as configured in git, are pushed. Branches that have diverged from their upstreams are left alone.
+
+If no <path>s are given, push in all projects.")
+        .arg(super::jobs_arg())
+        .arg(super::format_arg())
+        .arg(
+            Arg::with_name("only-changes")
+                .long("only-changes")
+                .help("Only print out-of-sync or pushed repositories and branches"),
+        )
+        .arg(
+            Arg::with_name("dry-run")
+                .short("n")
+                .long("dry-run")
+                .help("Only print which branches would be pushed"),
+        )
+        .arg(
+            Arg::with_name("only-head")
+                .long("only-head")
+                .help("Only push the checked out branch in each project"),
+        )
+        .arg(super::group_arg())
+        .arg(super::here_arg())
+        .arg(super::no_here_arg())
+        .arg(super::exclude_group_arg())
+        .arg(Arg::with_name("path").multiple(true).help(
+            "Project paths or glob patterns to be pushed, relative to the current directory",
+        ))
+}
+
+pub fn make_command(matches: &ArgMatches, config: &UserConfig, ctx: &WorkspaceContext) -> Push {
+    Push {
+        status_command: super::status::make_command(matches, config, ctx),
+        jobs: super::jobs_value(matches, config),
+        credentials: super::credentials_value(config),
+        dry_run: matches.is_present("dry-run"),
+        only_head: matches.is_present("only-head"),
+    }
+}
+
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::RepositoryCommand(Box::new(make_command(matches, config, ctx)))
+}
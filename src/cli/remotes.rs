@@ -0,0 +1,49 @@
+use clap::App;
+use clap::AppSettings;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::remotes::SyncRemotes;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("remotes")
+        .about("Manage the remotes of existing repositories")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("sync")
+                .about("Make the remotes of existing repositories match the projects list")
+                .after_help(
+                    "Missing remotes are added and changed URLs are updated. Remotes that are not in the projects list are reported but never removed. If no <path>s are given, sync all projects.",
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .short("n")
+                        .long("dry-run")
+                        .help("Only print what would be changed"),
+                )
+                .arg(super::group_arg())
+                .arg(super::exclude_group_arg())
+                .arg(Arg::with_name("path").multiple(true).help(
+                    "Project paths or glob patterns to be synced, relative to the current directory",
+                )),
+        )
+}
+
+pub fn make_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> SyncRemotes {
+    SyncRemotes {
+        projects: super::project_filter(matches, ctx, super::Here::Off),
+        dry_run: matches.is_present("dry-run"),
+    }
+}
+
+pub fn make_cli_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Command {
+    match matches.subcommand() {
+        ("sync", Some(sync_matches)) => {
+            Command::DirectoryCommand(Box::new(make_command(sync_matches, ctx)))
+        }
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,35 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::restore::Restore;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("restore")
+        .about("Check out the commits recorded by freeze in each project")
+        .after_help("A recorded branch is checked out if it still points to the recorded commit. Otherwise the commit is checked out as a detached HEAD, and no branches are moved.
+
+Repositories with uncommitted changes or an operation in progress are not restored.")
+        .arg(
+            Arg::with_name("lockfile")
+                .required(true)
+                .help("Lockfile written by freeze, relative to the current directory"),
+        )
+}
+
+pub fn make_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Restore {
+    Restore {
+        lockfile: ctx.working_dir.join(
+            matches
+                .value_of("lockfile")
+                .expect("lockfile argument required"),
+        ),
+    }
+}
+
+pub fn make_cli_command(matches: &ArgMatches, ctx: &WorkspaceContext) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, ctx)))
+}
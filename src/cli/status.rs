@@ -3,34 +3,40 @@ use clap::Arg;
 use clap::ArgMatches;
 use clap::SubCommand;
 
+use super::WorkspaceContext;
 use crate::commands::common::Command;
 use crate::commands::status::Status;
+use crate::config::data::user_config::UserConfig;
 
 pub fn subcommand_def<'a>() -> App<'a, 'a> {
     SubCommand::with_name("status")
         .about("Print status for all repositories in the workspace")
+        .arg(super::format_arg())
         .arg(
             Arg::with_name("only-changes")
                 .long("only-changes")
                 .help("Only print out-of-sync repositories and branches"),
         )
-        .arg(
-            Arg::with_name("path")
-                .multiple(true)
-                .help("Project paths to show status for"),
-        )
+        .arg(super::group_arg())
+        .arg(super::here_arg())
+        .arg(super::no_here_arg())
+        .arg(super::exclude_group_arg())
+        .arg(Arg::with_name("path").multiple(true).help(
+            "Project paths or glob patterns to show status for, relative to the current directory",
+        ))
 }
 
-pub fn make_command(matches: &ArgMatches) -> Status {
+pub fn make_command(matches: &ArgMatches, config: &UserConfig, ctx: &WorkspaceContext) -> Status {
     Status {
         only_changes: matches.is_present("only-changes"),
-        projects: matches
-            .values_of("path")
-            .map(|values| values.map(&str::to_string).collect())
-            .unwrap_or_default(),
+        projects: super::project_filter(matches, ctx, super::here_value(matches, config)),
     }
 }
 
-pub fn make_cli_command(matches: &ArgMatches) -> Command {
-    Command::RepositoryCommand(Box::new(make_command(matches)))
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::RepositoryCommand(Box::new(make_command(matches, config, ctx)))
 }
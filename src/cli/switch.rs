@@ -0,0 +1,47 @@
+use clap::App;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::SubCommand;
+
+use super::WorkspaceContext;
+use crate::commands::common::Command;
+use crate::commands::switch::Switch;
+use crate::config::data::user_config::UserConfig;
+
+pub fn subcommand_def<'a>() -> App<'a, 'a> {
+    SubCommand::with_name("switch")
+        .about("Check out a branch in each project that has it")
+        .after_help("If the local branch does not exist but the branch exists on the main remote of a project, a local branch tracking it is created. Projects with neither are left alone, and so are projects with uncommitted changes or an operation in progress.
+
+If no <path>s are given, switch in all projects.")
+        .arg(
+            Arg::with_name("branch")
+                .required(true)
+                .help("Name of the branch to check out"),
+        )
+        .arg(super::group_arg())
+        .arg(super::here_arg())
+        .arg(super::no_here_arg())
+        .arg(super::exclude_group_arg())
+        .arg(Arg::with_name("path").multiple(true).help(
+            "Project paths or glob patterns to switch branch in, relative to the current directory",
+        ))
+}
+
+pub fn make_command(matches: &ArgMatches, config: &UserConfig, ctx: &WorkspaceContext) -> Switch {
+    Switch {
+        projects: super::project_filter(matches, ctx, super::here_value(matches, config)),
+        branch: matches
+            .value_of("branch")
+            .expect("branch argument required")
+            .to_string(),
+    }
+}
+
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, config, ctx)))
+}
@@ -2,18 +2,31 @@ use clap::App;
 use clap::ArgMatches;
 use clap::SubCommand;
 
+use super::WorkspaceContext;
 use crate::commands::common::Command;
 use crate::commands::update::Update;
+use crate::config::data::user_config::UserConfig;
 
 pub fn subcommand_def<'a>() -> App<'a, 'a> {
     SubCommand::with_name("update")
         .about("Clone any repositories in the projects list that are missing in the workspace")
+        .arg(super::jobs_arg())
+        .arg(super::group_arg())
+        .arg(super::exclude_group_arg())
 }
 
-pub fn make_command(_matches: &ArgMatches) -> Update {
-    Update {}
+pub fn make_command(matches: &ArgMatches, config: &UserConfig, ctx: &WorkspaceContext) -> Update {
+    Update {
+        projects: super::project_filter(matches, ctx, super::Here::Off),
+        jobs: super::jobs_value(matches, config),
+        credentials: super::credentials_value(config),
+    }
 }
 
-pub fn make_cli_command(matches: &ArgMatches) -> Command {
-    Command::DirectoryCommand(Box::new(make_command(matches)))
+pub fn make_cli_command(
+    matches: &ArgMatches,
+    config: &UserConfig,
+    ctx: &WorkspaceContext,
+) -> Command {
+    Command::DirectoryCommand(Box::new(make_command(matches, config, ctx)))
 }
@@ -6,11 +6,15 @@ pub struct Palette {
     pub branch: Style,
     pub clean: Style,
     pub cloning: Style,
+    pub conflict: Style,
     pub dirty: Style,
     pub error: Style,
+    pub in_progress: Style,
     pub missing: Style,
     pub repo: Style,
     pub repo_exists: Style,
+    pub staged: Style,
+    pub stashed: Style,
 }
 
 impl Palette {
@@ -19,11 +23,15 @@ impl Palette {
             branch: Colour::Fixed(13).normal(),
             clean: Colour::Fixed(10).normal(),
             cloning: Colour::Fixed(14).normal(),
+            conflict: Colour::Fixed(1).normal(),
             dirty: Colour::Fixed(9).normal(),
             error: Colour::Fixed(9).normal(),
+            in_progress: Colour::Fixed(208).normal(),
             missing: Colour::Fixed(11).normal(),
             repo: Colour::Fixed(12).normal(),
             repo_exists: Colour::Fixed(10).normal(),
+            staged: Colour::Fixed(11).normal(),
+            stashed: Colour::Fixed(8).normal(),
         }
     }
 }
@@ -0,0 +1,178 @@
+use serde::Serialize;
+
+use super::common::default_remote_branch;
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
+use super::common::RepositoryCommand;
+use super::common::RepositoryReport;
+use super::error::Error;
+use super::status::Status;
+use crate::color::palette::Palette;
+use crate::config::data::Project;
+use crate::data::status::BranchCreation;
+use crate::data::status::BranchCreationOutcome;
+use crate::data::status::RepositoryStatus;
+
+pub struct CreateBranch {
+    pub status_command: Status,
+    /// The name of the branch to create.
+    pub name: String,
+    /// Start from the default branch of the main remote instead of `HEAD`.
+    pub from_default: bool,
+    /// Make the new branch track the branch with the same name on this
+    /// remote, if that branch exists.
+    pub upstream_remote: Option<String>,
+}
+
+impl CreateBranch {
+    fn create(&self, repo: &git2::Repository, project: &Project) -> BranchCreation {
+        let mut creation = BranchCreation {
+            branch: self.name.clone(),
+            start_point: String::new(),
+            upstream_name: None,
+            missing_upstream: None,
+            outcome: BranchCreationOutcome::Created,
+        };
+
+        if let Err(message) = self.try_create(repo, project, &mut creation) {
+            creation.outcome = BranchCreationOutcome::Failed(message);
+        }
+        creation
+    }
+
+    fn try_create(
+        &self,
+        repo: &git2::Repository,
+        project: &Project,
+        creation: &mut BranchCreation,
+    ) -> Result<(), String> {
+        if repo
+            .find_branch(&self.name, git2::BranchType::Local)
+            .is_ok()
+        {
+            creation.outcome = BranchCreationOutcome::AlreadyExists;
+            return Ok(());
+        }
+
+        let start = if self.from_default {
+            default_remote_branch(repo, project)?.into_reference()
+        } else {
+            repo.head().map_err(|e| e.message().to_string())?
+        };
+        creation.start_point = start.shorthand().unwrap_or("HEAD").to_string();
+        let commit = start
+            .peel_to_commit()
+            .map_err(|e| e.message().to_string())?;
+
+        if let Some(remote) = &self.upstream_remote {
+            repo.find_remote(remote)
+                .map_err(|_| format!("Remote {} not found", remote))?;
+        }
+
+        let mut branch = repo
+            .branch(&self.name, &commit, false)
+            .map_err(|e| e.message().to_string())?;
+
+        // Tracking a branch that doesn't exist on the remote would make the
+        // upstream look gone
+        if let Some(remote) = &self.upstream_remote {
+            let upstream_name = format!("{}/{}", remote, self.name);
+            if repo
+                .find_branch(&upstream_name, git2::BranchType::Remote)
+                .is_ok()
+            {
+                branch
+                    .set_upstream(Some(&upstream_name))
+                    .map_err(|e| e.message().to_string())?;
+                creation.upstream_name = Some(upstream_name);
+            } else {
+                creation.missing_upstream = Some(upstream_name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The status of a project after creating the branch in it, and how that
+/// went.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct BranchReport {
+    #[serde(flatten)]
+    pub status: RepositoryStatus,
+    pub created_branch: BranchCreation,
+}
+
+impl RepositoryReport for BranchReport {
+    fn status(&self) -> &RepositoryStatus {
+        &self.status
+    }
+
+    fn has_changes(&self) -> bool {
+        true
+    }
+
+    fn failed(&self) -> bool {
+        matches!(
+            self.created_branch.outcome,
+            BranchCreationOutcome::Failed(_)
+        )
+    }
+
+    fn print_results(&self, palette: &Palette) {
+        println!(
+            "{}",
+            describe_branch_creation(&self.created_branch, palette)
+        );
+    }
+}
+
+fn describe_branch_creation(creation: &BranchCreation, palette: &Palette) -> String {
+    let upstream = match (&creation.upstream_name, &creation.missing_upstream) {
+        (Some(upstream), _) => format!(", tracking {}", upstream),
+        (None, Some(missing)) => format!(
+            "; remote branch {} does not exist, upstream not set",
+            missing
+        ),
+        (None, None) => String::new(),
+    };
+    match &creation.outcome {
+        BranchCreationOutcome::Created => palette.cloning.paint(format_message_line(&format!(
+            "Created branch {} from {}{}",
+            creation.branch, creation.start_point, upstream
+        ))),
+        BranchCreationOutcome::AlreadyExists => palette.dirty.paint(format_message_line(&format!(
+            "Branch {} already exists",
+            creation.branch
+        ))),
+        BranchCreationOutcome::Failed(message) => palette.error.paint(format_message_line(
+            &format!("Failed to create branch {}: {}", creation.branch, message),
+        )),
+    }
+    .to_string()
+}
+
+impl RepositoryCommand for CreateBranch {
+    type Report = BranchReport;
+
+    fn only_changes(&self) -> bool {
+        self.status_command.only_changes()
+    }
+
+    fn project_filter(&self) -> &ProjectFilter {
+        self.status_command.project_filter()
+    }
+
+    fn run_project(
+        &self,
+        project: &Project,
+        repository: &git2::Repository,
+    ) -> Result<BranchReport, Error> {
+        let created_branch = self.create(repository, project);
+        let status = self.status_command.run_project(project, repository)?;
+        Ok(BranchReport {
+            status,
+            created_branch,
+        })
+    }
+}
@@ -1 +1,112 @@
+use std::collections::BTreeMap;
+use std::path::Path;
 
+use super::common::exit_codes;
+use super::common::find_repositories;
+use super::common::format_message_line;
+use super::common::format_path_header;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Workspace;
+
+pub struct Check {}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RepositoryPresence {
+    Ignored,
+    Known,
+    Missing,
+    /// A listed project whose path exists but is not a git repository.
+    NotARepository,
+    Unknown,
+}
+
+impl RepositoryPresence {
+    pub fn is_consistent(&self) -> bool {
+        match self {
+            RepositoryPresence::Ignored | RepositoryPresence::Known => true,
+            RepositoryPresence::Missing
+            | RepositoryPresence::NotARepository
+            | RepositoryPresence::Unknown => false,
+        }
+    }
+}
+
+impl Check {
+    pub fn make_report(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+    ) -> Result<BTreeMap<String, RepositoryPresence>, Error> {
+        let mut report: BTreeMap<String, RepositoryPresence> = workspace
+            .projects
+            .iter()
+            .map(|project| {
+                let project_dir = working_dir.join(&project.path);
+                (
+                    project.path.clone(),
+                    if !project_dir.exists() {
+                        RepositoryPresence::Missing
+                    } else if git2::Repository::open(&project_dir).is_ok() {
+                        RepositoryPresence::Known
+                    } else {
+                        RepositoryPresence::NotARepository
+                    },
+                )
+            })
+            .collect();
+
+        for path in find_repositories(working_dir)? {
+            let presence = if workspace.ignore.is_ignored(&path) {
+                RepositoryPresence::Ignored
+            } else {
+                RepositoryPresence::Unknown
+            };
+            report.entry(path).or_insert(presence);
+        }
+
+        Ok(report)
+    }
+}
+
+fn print_presence(path: &str, presence: RepositoryPresence, palette: &Palette) {
+    println!("{}", format_path_header(path, palette));
+    println!(
+        "{}",
+        match presence {
+            RepositoryPresence::Ignored => palette.missing.paint(format_message_line("Ignored")),
+            RepositoryPresence::Known => palette.clean.paint(format_message_line("Known")),
+            RepositoryPresence::Missing => palette
+                .missing
+                .paint(format_message_line("Missing repository")),
+            RepositoryPresence::NotARepository => palette
+                .error
+                .paint(format_message_line("Not a git repository")),
+            RepositoryPresence::Unknown => palette
+                .dirty
+                .paint(format_message_line("Unknown repository")),
+        }
+    );
+}
+
+impl DirectoryCommand for Check {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        let report = self.make_report(working_dir, workspace)?;
+
+        for (path, presence) in &report {
+            print_presence(path, *presence, palette);
+        }
+
+        Ok(if report.values().all(RepositoryPresence::is_consistent) {
+            exit_codes::OK
+        } else {
+            exit_codes::INCONSISTENT_WORKSPACE
+        })
+    }
+}
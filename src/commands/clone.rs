@@ -1,18 +1,168 @@
-use std::collections::HashSet;
 use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
+use super::common::check_path_args;
+use super::common::credentials::Credentials;
 use super::common::exit_codes;
+use super::common::filter::ProjectFilter;
 use super::common::format_message_line;
 use super::common::format_project_header;
 use super::common::get_repobuilder;
+use super::common::progress;
 use super::common::update_submodules;
 use super::common::DirectoryCommand;
 use super::error::Error;
 use crate::color::palette::Palette;
+use crate::config::data::Project;
 use crate::config::data::Workspace;
+use crate::util::parallel::for_each_in_order;
 
 pub struct Clone {
-    pub projects: HashSet<String>,
+    /// Projects to clone. Nothing is cloned if this is empty.
+    pub projects: ProjectFilter,
+    pub jobs: usize,
+    pub credentials: Credentials,
+}
+
+enum CloneResult {
+    AlreadyExists,
+    Cloned {
+        add_remote_errors: Vec<(String, git2::Error)>,
+    },
+    CloneFailed(git2::Error),
+    SubmodulesFailed(git2::Error),
+}
+
+fn clone_project(
+    working_dir: &Path,
+    project: &Project,
+    credentials: &Credentials,
+    jobs: usize,
+) -> CloneResult {
+    if working_dir.join(&project.path).exists() {
+        return CloneResult::AlreadyExists;
+    }
+
+    match get_repobuilder(credentials, &project.path, jobs)
+        .clone(&project.main_remote.url, &working_dir.join(&project.path))
+    {
+        Ok(repo) => {
+            if project.submodules {
+                if let Err(err) = update_submodules(&repo, credentials) {
+                    return CloneResult::SubmodulesFailed(err);
+                }
+            }
+            CloneResult::Cloned {
+                add_remote_errors: project
+                    .extra_remotes
+                    .iter()
+                    .flat_map(|extra_remote| {
+                        repo.remote(&extra_remote.name, &extra_remote.url)
+                            .err()
+                            .map(|err| (extra_remote.name.clone(), err))
+                    })
+                    .collect(),
+            }
+        }
+        Err(err) => CloneResult::CloneFailed(err),
+    }
+}
+
+fn print_result(project: &Project, result: &CloneResult, palette: &Palette) {
+    progress::clear_line();
+    println!("{}", format_project_header(project, palette));
+
+    if !matches!(result, CloneResult::AlreadyExists) {
+        println!("{}", palette.cloning.paint(format_message_line("Cloning…")));
+    }
+
+    match result {
+        CloneResult::AlreadyExists => {
+            println!(
+                "{}",
+                palette.clean.paint(format_message_line("Already exists"))
+            );
+        }
+        CloneResult::Cloned { add_remote_errors } => {
+            for (name, err) in add_remote_errors {
+                eprintln!("Failed to add remote {}: {}", name, err);
+            }
+            println!("{}", palette.clean.paint(format_message_line("Cloned.")));
+        }
+        CloneResult::CloneFailed(err) => {
+            eprintln!("Failed to clone project {}: {}", project.path, err);
+            if err.class() == git2::ErrorClass::Net {
+                eprintln!("Have you tried cloning via SSH, or setting up a git credential helper?");
+            }
+            println!("{}", palette.error.paint(format_message_line("Error")));
+        }
+        CloneResult::SubmodulesFailed(err) => {
+            eprintln!(
+                "Failed to update submodules in project {}: {}",
+                project.path, err
+            );
+            println!("{}", palette.error.paint(format_message_line("Error")));
+        }
+    }
+}
+
+/// Clone those of `projects` that are missing in the workspace, up to `jobs`
+/// at a time. If updating the submodules of a clone fails, no more clones are
+/// started, and the error is returned once the clones in progress finish.
+pub fn clone_projects(
+    projects: Vec<&Project>,
+    jobs: usize,
+    credentials: &Credentials,
+    working_dir: &Path,
+    palette: &Palette,
+) -> Result<i32, Error> {
+    let mut clone_failed: bool = false;
+    let mut add_remote_failed: bool = false;
+    let mut submodules_error: Option<git2::Error> = None;
+    let stopped = AtomicBool::new(false);
+
+    for_each_in_order(
+        projects,
+        jobs,
+        |project| {
+            if stopped.load(Ordering::SeqCst) {
+                return None;
+            }
+            let result = clone_project(working_dir, project, credentials, jobs);
+            if matches!(result, CloneResult::SubmodulesFailed(_)) {
+                stopped.store(true, Ordering::SeqCst);
+            }
+            Some((*project, result))
+        },
+        |outcome| {
+            if let Some((project, result)) = outcome {
+                print_result(project, &result, palette);
+                match result {
+                    CloneResult::AlreadyExists => {}
+                    CloneResult::Cloned { add_remote_errors } => {
+                        add_remote_failed = add_remote_failed || !add_remote_errors.is_empty();
+                    }
+                    CloneResult::CloneFailed(_) => {
+                        clone_failed = true;
+                    }
+                    CloneResult::SubmodulesFailed(err) => {
+                        submodules_error.get_or_insert(err);
+                    }
+                }
+            }
+        },
+    );
+
+    if let Some(err) = submodules_error {
+        return Err(Error::from(err));
+    }
+
+    Ok(if clone_failed || add_remote_failed {
+        exit_codes::INTERNAL_ERROR
+    } else {
+        exit_codes::OK
+    })
 }
 
 impl DirectoryCommand for Clone {
@@ -22,59 +172,16 @@ impl DirectoryCommand for Clone {
         workspace: &Workspace,
         palette: &Palette,
     ) -> Result<i32, Error> {
-        let mut clone_failed: bool = false;
-        let mut add_remote_failed: bool = false;
+        if let Some(exit_code) = check_path_args(&self.projects, workspace) {
+            return Ok(exit_code);
+        }
 
-        for project in workspace
+        let projects: Vec<&Project> = workspace
             .projects
             .iter()
-            .filter(|proj| self.projects.contains(&proj.path))
-        {
-            println!("{}", format_project_header(project, palette));
-
-            if working_dir.join(&project.path).exists() {
-                println!(
-                    "{}",
-                    palette.clean.paint(format_message_line("Already exists"))
-                );
-            } else {
-                println!("{}", palette.cloning.paint(format_message_line("Cloning…")));
-
-                match get_repobuilder()
-                    .clone(&project.main_remote.url, &working_dir.join(&project.path))
-                {
-                    Ok(repo) => {
-                        update_submodules(&repo)?;
-                        for extra_remote in &project.extra_remotes {
-                            match repo.remote(&extra_remote.name, &extra_remote.url) {
-                                Ok(_) => {}
-                                Err(err) => {
-                                    add_remote_failed = true;
-                                    eprintln!(
-                                        "Failed to add remote {}: {}",
-                                        extra_remote.name, err
-                                    );
-                                }
-                            }
-                        }
-                        println!("{}", palette.clean.paint(format_message_line("Cloned.")));
-                    }
-                    Err(err) => {
-                        clone_failed = true;
-                        eprintln!("Failed to clone project {}: {}", project.path, err);
-                        if err.class() == git2::ErrorClass::Net {
-                            eprintln!("Have you tried cloning via SSH, or setting up a git credential helper?");
-                        }
-                        println!("{}", palette.error.paint(format_message_line("Error")));
-                    }
-                }
-            }
-        }
+            .filter(|proj| !self.projects.is_empty() && self.projects.matches(proj))
+            .collect();
 
-        Ok(if clone_failed || add_remote_failed {
-            exit_codes::INTERNAL_ERROR
-        } else {
-            exit_codes::OK
-        })
+        clone_projects(projects, self.jobs, &self.credentials, working_dir, palette)
     }
 }
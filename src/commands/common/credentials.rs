@@ -0,0 +1,134 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use git2::CredentialType;
+
+/// How to authenticate to remotes when fetching, cloning and pushing.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Credentials {
+    /// Private SSH keys to try, in order, after the SSH agent. A public key
+    /// next to a private key, with `.pub` appended to its name, is used if
+    /// present.
+    pub ssh_keys: Vec<PathBuf>,
+}
+
+fn public_key_path(private_key: &Path) -> Option<PathBuf> {
+    let mut file_name = private_key.file_name()?.to_os_string();
+    file_name.push(".pub");
+    Some(private_key.with_file_name(file_name)).filter(|path| path.exists())
+}
+
+/// A way to answer a credential request.
+#[derive(Debug, Eq, PartialEq)]
+enum Attempt {
+    Username,
+    SshAgent,
+    /// The SSH key at this index in `Credentials::ssh_keys`.
+    SshKey(usize),
+    CredentialHelper,
+    GiveUp,
+}
+
+/// The methods tried so far while authenticating to one remote.
+#[derive(Default)]
+struct Attempts {
+    tried_username: bool,
+    tried_agent: bool,
+    next_ssh_key: usize,
+    tried_password: bool,
+}
+
+impl Attempts {
+    /// The next method to try among those `allowed`, given `ssh_key_count`
+    /// configured SSH keys.
+    fn next(&mut self, allowed: CredentialType, ssh_key_count: usize) -> Attempt {
+        if allowed.contains(CredentialType::USERNAME) && !self.tried_username {
+            self.tried_username = true;
+            Attempt::Username
+        } else if allowed.contains(CredentialType::SSH_KEY) && !self.tried_agent {
+            self.tried_agent = true;
+            Attempt::SshAgent
+        } else if allowed.contains(CredentialType::SSH_KEY) && self.next_ssh_key < ssh_key_count {
+            self.next_ssh_key += 1;
+            Attempt::SshKey(self.next_ssh_key - 1)
+        } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && !self.tried_password {
+            self.tried_password = true;
+            Attempt::CredentialHelper
+        } else {
+            Attempt::GiveUp
+        }
+    }
+}
+
+impl Credentials {
+    /// Callbacks that answer credential requests by trying, in order: the SSH
+    /// agent, each of `ssh_keys`, and the git credential helper. Each method,
+    /// and sending just the username, is tried only once, so that
+    /// authentication fails instead of looping when nothing is accepted.
+    pub fn remote_callbacks<'a>(&self) -> git2::RemoteCallbacks<'a> {
+        let ssh_keys = self.ssh_keys.clone();
+        let mut attempts = Attempts::default();
+
+        let mut callbacks = git2::RemoteCallbacks::new();
+        callbacks.credentials(
+            move |url: &str, username: Option<&str>, allowed: CredentialType| {
+                let config = git2::Config::open_default()?;
+                let mut cred_helper = git2::CredentialHelper::new(url);
+                cred_helper.config(&config);
+                let user: String = username
+                    .map(str::to_string)
+                    .or_else(|| cred_helper.username.clone())
+                    .unwrap_or_else(|| "git".to_string());
+
+                match attempts.next(allowed, ssh_keys.len()) {
+                    Attempt::Username => git2::Cred::username(&user),
+                    Attempt::SshAgent => git2::Cred::ssh_key_from_agent(&user),
+                    Attempt::SshKey(index) => git2::Cred::ssh_key(
+                        &user,
+                        public_key_path(&ssh_keys[index]).as_deref(),
+                        &ssh_keys[index],
+                        None,
+                    ),
+                    Attempt::CredentialHelper => {
+                        git2::Cred::credential_helper(&config, url, username)
+                    }
+                    Attempt::GiveUp => git2::Cred::default(),
+                }
+            },
+        );
+        callbacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::CredentialType;
+
+    use super::Attempt;
+    use super::Attempts;
+
+    #[test]
+    fn ssh_methods_are_tried_in_order_once_each() {
+        let mut attempts = Attempts::default();
+        let ssh = CredentialType::SSH_KEY | CredentialType::USER_PASS_PLAINTEXT;
+        assert_eq!(
+            attempts.next(CredentialType::USERNAME, 2),
+            Attempt::Username
+        );
+        assert_eq!(attempts.next(ssh, 2), Attempt::SshAgent);
+        assert_eq!(attempts.next(ssh, 2), Attempt::SshKey(0));
+        assert_eq!(attempts.next(ssh, 2), Attempt::SshKey(1));
+        assert_eq!(attempts.next(ssh, 2), Attempt::CredentialHelper);
+        assert_eq!(attempts.next(ssh, 2), Attempt::GiveUp);
+    }
+
+    #[test]
+    fn username_is_sent_only_once() {
+        let mut attempts = Attempts::default();
+        assert_eq!(
+            attempts.next(CredentialType::USERNAME, 0),
+            Attempt::Username
+        );
+        assert_eq!(attempts.next(CredentialType::USERNAME, 0), Attempt::GiveUp);
+    }
+}
@@ -4,3 +4,4 @@ pub const OK: ExitCode = 0;
 pub const UNKNOWN_ERROR: ExitCode = 1;
 pub const INTERNAL_ERROR: ExitCode = 2;
 pub const USER_ERROR: ExitCode = 3;
+pub const INCONSISTENT_WORKSPACE: ExitCode = 4;
@@ -0,0 +1,328 @@
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::config::data::Project;
+
+/// A project path argument. Selects the projects at or below the paths it
+/// matches. `*` and `?` match within a single path segment and `**` matches
+/// across segments.
+#[derive(Debug)]
+pub struct PathPattern {
+    /// The argument as given on the command line.
+    pub arg: String,
+    /// `None` if the argument resolves to outside the workspace.
+    regex: Option<Regex>,
+}
+
+/// Resolve `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !result.pop() {
+                    result.push(component);
+                }
+            }
+            _ => result.push(component),
+        }
+    }
+    result
+}
+
+/// `path` relative to `workspace_dir` with `/` separators, like `Project::path`,
+/// or `None` if `path` is outside `workspace_dir`.
+fn workspace_relative(path: &Path, workspace_dir: &Path) -> Option<String> {
+    normalize(path)
+        .strip_prefix(normalize(workspace_dir))
+        .ok()
+        .and_then(|relative| relative.to_str())
+        .map(|relative| {
+            relative
+                .split(std::path::MAIN_SEPARATOR)
+                .collect::<Vec<&str>>()
+                .join("/")
+        })
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut result = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                result.push_str(".*");
+            }
+            '*' => result.push_str("[^/]*"),
+            '?' => result.push_str("[^/]"),
+            c => result.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    result
+}
+
+impl PathPattern {
+    /// Interpret `arg` relative to `working_dir`, and match it against project
+    /// paths relative to `workspace_dir`.
+    pub fn new(arg: &str, working_dir: &Path, workspace_dir: &Path) -> PathPattern {
+        let regex = workspace_relative(&working_dir.join(arg), workspace_dir).map(|pattern| {
+            Regex::new(&if pattern.is_empty() {
+                "^".to_string()
+            } else {
+                format!("^{}(/|$)", glob_to_regex(&pattern))
+            })
+            .expect("Glob pattern should always translate to a valid regex")
+        });
+
+        PathPattern {
+            arg: arg.to_string(),
+            regex,
+        }
+    }
+
+    /// Match only the innermost project containing `working_dir`, or nothing
+    /// if `working_dir` is not inside any of `projects`.
+    pub fn enclosing_project<'p, I>(
+        working_dir: &Path,
+        workspace_dir: &Path,
+        projects: I,
+    ) -> PathPattern
+    where
+        I: IntoIterator<Item = &'p Project>,
+    {
+        let enclosing: Option<&Project> =
+            workspace_relative(working_dir, workspace_dir).and_then(|relative| {
+                projects
+                    .into_iter()
+                    .filter(|project| {
+                        relative == project.path
+                            || relative.starts_with(&format!("{}/", project.path))
+                    })
+                    .max_by_key(|project| project.path.len())
+            });
+
+        PathPattern {
+            arg: working_dir.to_string_lossy().to_string(),
+            regex: enclosing.map(|project| {
+                Regex::new(&format!("^{}$", regex::escape(&project.path)))
+                    .expect("Escaped path should always be a valid regex")
+            }),
+        }
+    }
+
+    pub fn matches(&self, project_path: &str) -> bool {
+        self.regex
+            .as_ref()
+            .map(|regex| regex.is_match(project_path))
+            .unwrap_or(false)
+    }
+}
+
+/// Selects projects by path and by group membership.
+#[derive(Debug, Default)]
+pub struct ProjectFilter {
+    /// If nonempty, only projects matching one of these patterns are selected.
+    pub paths: Vec<PathPattern>,
+    /// If nonempty, only projects in at least one of these groups are selected.
+    pub groups: HashSet<String>,
+    /// Projects in any of these groups are never selected.
+    pub exclude_groups: HashSet<String>,
+}
+
+impl ProjectFilter {
+    /// Whether this filter has no criteria, and therefore selects all projects.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.groups.is_empty() && self.exclude_groups.is_empty()
+    }
+
+    pub fn matches(&self, project: &Project) -> bool {
+        (self.paths.is_empty() || self.paths.iter().any(|p| p.matches(&project.path)))
+            && (self.groups.is_empty() || project.groups.iter().any(|g| self.groups.contains(g)))
+            && !project
+                .groups
+                .iter()
+                .any(|g| self.exclude_groups.contains(g))
+    }
+
+    /// The path arguments that match none of `projects`.
+    pub fn unmatched_paths<'a, 'p, I>(&'a self, projects: I) -> Vec<&'a str>
+    where
+        I: IntoIterator<Item = &'p Project>,
+    {
+        let projects: Vec<&Project> = projects.into_iter().collect();
+        self.paths
+            .iter()
+            .filter(|pattern| !projects.iter().any(|p| pattern.matches(&p.path)))
+            .map(|pattern| pattern.arg.as_str())
+            .collect()
+    }
+}
+
+impl From<HashSet<String>> for ProjectFilter {
+    fn from(paths: HashSet<String>) -> Self {
+        ProjectFilter {
+            paths: paths
+                .iter()
+                .map(|path| PathPattern::new(path, Path::new(""), Path::new("")))
+                .collect(),
+            ..ProjectFilter::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::Path;
+
+    use super::PathPattern;
+    use super::ProjectFilter;
+    use crate::config::data::Project;
+    use crate::config::data::Remote;
+
+    fn project(path: &str, groups: &[&str]) -> Project {
+        Project {
+            path: path.to_string(),
+            default_branch: None,
+            groups: groups.iter().map(|g| g.to_string()).collect(),
+            submodules: true,
+            main_remote: Remote {
+                name: "origin".to_string(),
+                url: format!("https://example.org/{}.git", path),
+            },
+            extra_remotes: vec![],
+        }
+    }
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn pattern(arg: &str) -> PathPattern {
+        PathPattern::new(arg, Path::new("/ws"), Path::new("/ws"))
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let filter = ProjectFilter::default();
+        assert!(filter.is_empty());
+        assert!(filter.matches(&project("foo", &[])));
+        assert!(filter.matches(&project("bar", &["work"])));
+    }
+
+    #[test]
+    fn groups_select_projects_in_any_group() {
+        let filter = ProjectFilter {
+            groups: set(&["work", "infra"]),
+            ..ProjectFilter::default()
+        };
+        assert!(filter.matches(&project("a", &["work"])));
+        assert!(filter.matches(&project("b", &["oss", "infra"])));
+        assert!(!filter.matches(&project("c", &["oss"])));
+        assert!(!filter.matches(&project("d", &[])));
+    }
+
+    #[test]
+    fn exclude_groups_take_precedence() {
+        let filter = ProjectFilter {
+            groups: set(&["work"]),
+            exclude_groups: set(&["oss"]),
+            ..ProjectFilter::from(set(&["a", "b"]))
+        };
+        assert!(filter.matches(&project("a", &["work"])));
+        assert!(!filter.matches(&project("b", &["work", "oss"])));
+        assert!(!filter.matches(&project("c", &["work"])));
+    }
+
+    #[test]
+    fn path_matches_project_and_projects_below_it() {
+        assert!(pattern("contrib").matches("contrib"));
+        assert!(pattern("contrib/").matches("contrib/gws"));
+        assert!(pattern("contrib").matches("contrib/gws/sub"));
+        assert!(!pattern("contrib").matches("contributions"));
+        assert!(!pattern("contrib/gws").matches("contrib"));
+    }
+
+    #[test]
+    fn globs_match_within_and_across_segments() {
+        assert!(pattern("work/*").matches("work/foo"));
+        assert!(pattern("work/*").matches("work/foo/bar"));
+        assert!(!pattern("work/*").matches("work"));
+        assert!(pattern("*/gws").matches("contrib/gws"));
+        assert!(!pattern("*/gws").matches("a/contrib/gws"));
+        assert!(pattern("**/gws").matches("a/contrib/gws"));
+        assert!(pattern("w?rk").matches("work"));
+        assert!(!pattern("w?rk").matches("w/rk"));
+        assert!(!pattern("a.b").matches("axb"));
+    }
+
+    #[test]
+    fn relative_paths_are_resolved_from_working_dir() {
+        let here = PathPattern::new(".", Path::new("/ws/contrib"), Path::new("/ws"));
+        assert!(here.matches("contrib/gws"));
+        assert!(!here.matches("work/foo"));
+
+        let sibling = PathPattern::new("../work", Path::new("/ws/contrib"), Path::new("/ws"));
+        assert!(sibling.matches("work/foo"));
+        assert!(!sibling.matches("contrib/gws"));
+
+        let root = PathPattern::new("..", Path::new("/ws/contrib"), Path::new("/ws"));
+        assert!(root.matches("work/foo"));
+        assert!(root.matches("contrib/gws"));
+
+        let absolute = PathPattern::new("/ws/work", Path::new("/ws/contrib"), Path::new("/ws"));
+        assert!(absolute.matches("work/foo"));
+    }
+
+    #[test]
+    fn paths_outside_workspace_match_nothing() {
+        let outside = PathPattern::new("../..", Path::new("/ws/contrib"), Path::new("/ws"));
+        assert!(!outside.matches("contrib/gws"));
+        assert!(!outside.matches(""));
+    }
+
+    #[test]
+    fn enclosing_project_is_innermost_project_containing_working_dir() {
+        let projects = vec![
+            project("contrib", &[]),
+            project("contrib/gws", &[]),
+            project("work/foo", &[]),
+        ];
+
+        let in_gws = PathPattern::enclosing_project(
+            Path::new("/ws/contrib/gws/src"),
+            Path::new("/ws"),
+            &projects,
+        );
+        assert!(in_gws.matches("contrib/gws"));
+        assert!(!in_gws.matches("contrib"));
+
+        let in_contrib = PathPattern::enclosing_project(
+            Path::new("/ws/contrib/doc"),
+            Path::new("/ws"),
+            &projects,
+        );
+        assert!(in_contrib.matches("contrib"));
+        assert!(!in_contrib.matches("contrib/gws"));
+
+        let outside =
+            PathPattern::enclosing_project(Path::new("/ws/work"), Path::new("/ws"), &projects);
+        assert!(!outside.matches("work/foo"));
+        assert!(!outside.matches(""));
+    }
+
+    #[test]
+    fn unmatched_paths_are_reported() {
+        let projects = vec![project("contrib/gws", &[]), project("work/foo", &[])];
+        let filter = ProjectFilter {
+            paths: vec![pattern("work"), pattern("nope"), pattern("contrib/*")],
+            ..ProjectFilter::default()
+        };
+        assert_eq!(filter.unmatched_paths(&projects), vec!["nope"]);
+    }
+}
@@ -1,22 +1,34 @@
+pub mod credentials;
 pub mod exit_codes;
+pub mod filter;
+pub mod progress;
 
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 use std::path::Path;
 
 use ansi_term::ANSIString;
+use serde::Serialize;
 
+use self::credentials::Credentials;
+use self::filter::ProjectFilter;
 use super::error::Error;
 use crate::color::palette::Palette;
 use crate::config::data::Project;
 use crate::config::data::Workspace;
+use crate::data::serialize::write_workspace_status;
+use crate::data::serialize::SerializedFormat;
 use crate::data::status::BranchStatus;
+use crate::data::status::DetachedHead;
 use crate::data::status::DirtyState;
+use crate::data::status::Head;
+use crate::data::status::OngoingOperation;
 use crate::data::status::RepositoryStatus;
 use crate::data::status::WorkspaceStatus;
+use crate::util::parallel::for_each_in_order;
 
 pub enum Command {
     DirectoryCommand(Box<dyn DirectoryCommand>),
-    RepositoryCommand(Box<dyn RepositoryCommand>),
+    RepositoryCommand(Box<dyn AnyRepositoryCommand>),
 }
 
 pub trait DirectoryCommand {
@@ -28,10 +40,43 @@ pub trait DirectoryCommand {
     ) -> Result<i32, Error>;
 }
 
-pub trait RepositoryCommand {
+/// The result of a `RepositoryCommand` in one project: the status of the
+/// project's branches, and what the command did.
+pub trait RepositoryReport: Serialize + Send {
+    fn status(&self) -> &RepositoryStatus;
+
+    /// Whether the command did something worth showing even with
+    /// `--only-changes`.
+    fn has_changes(&self) -> bool {
+        false
+    }
+
+    /// Whether the command failed in part of the project.
+    fn failed(&self) -> bool {
+        false
+    }
+
+    /// Print what the command did, below the status of the branches.
+    fn print_results(&self, _palette: &Palette) {}
+}
+
+pub trait RepositoryCommand: Sync {
+    type Report: RepositoryReport;
+
     fn only_changes(&self) -> bool;
 
-    fn project_args(&self) -> &HashSet<String>;
+    /// Maximum number of projects to process concurrently.
+    fn jobs(&self) -> usize {
+        1
+    }
+
+    fn project_filter(&self) -> &ProjectFilter;
+
+    /// Print an error for each argument that `workspace` can't satisfy.
+    /// Returns the exit code to fail with, if any.
+    fn check_args(&self, workspace: &Workspace) -> Option<i32> {
+        check_path_args(self.project_filter(), workspace)
+    }
 
     fn run(
         &self,
@@ -39,23 +84,25 @@ pub trait RepositoryCommand {
         workspace: &Workspace,
         palette: &Palette,
     ) -> Result<i32, Error> {
+        if let Some(exit_code) = self.check_args(workspace) {
+            return Ok(exit_code);
+        }
         let reports = self.make_report_and_maybe_print(working_dir, workspace, Some(palette));
+        Ok(report_exit_code(&reports))
+    }
 
-        let exit_code = reports
-            .iter()
-            .map(|(_, project_result)| match project_result {
-                Ok(_) => exit_codes::OK,
-                Err(Error::RepositoryMissing) => exit_codes::OK,
-                Err(_) => exit_codes::INTERNAL_ERROR,
-            })
-            .fold(exit_codes::OK, |exit_code, next_code| {
-                if next_code != exit_codes::OK {
-                    next_code
-                } else {
-                    exit_code
-                }
-            });
-        Ok(exit_code)
+    fn run_serialized(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        format: SerializedFormat,
+    ) -> Result<i32, Error> {
+        if let Some(exit_code) = self.check_args(workspace) {
+            return Ok(exit_code);
+        }
+        let reports = self.make_report(working_dir, workspace);
+        write_workspace_status(&mut std::io::stdout().lock(), &reports, format)?;
+        Ok(report_exit_code(&reports))
     }
 
     fn make_report_and_maybe_print<'ws>(
@@ -63,17 +110,22 @@ pub trait RepositoryCommand {
         working_dir: &Path,
         workspace: &'ws Workspace,
         palette: Option<&Palette>,
-    ) -> WorkspaceStatus<'ws> {
-        workspace
+    ) -> WorkspaceStatus<'ws, Self::Report> {
+        let projects: Vec<&'ws Project> = workspace
             .projects
             .iter()
-            .filter(|project| {
-                self.project_args().is_empty() || self.project_args().contains(&project.path)
-            })
-            .map(|project| {
+            .filter(|project| self.project_filter().matches(project))
+            .collect();
+
+        let mut report = WorkspaceStatus::new();
+
+        for_each_in_order(
+            projects,
+            self.jobs(),
+            |project| {
                 let project_path: &Path = &working_dir.join(&project.path);
                 (
-                    project,
+                    *project,
                     if project_path.exists() {
                         git2::Repository::open(project_path)
                             .map_err(Error::from)
@@ -82,31 +134,38 @@ pub trait RepositoryCommand {
                         Err(Error::RepositoryMissing)
                     },
                 )
-            })
-            .filter(|(_, status_result)| {
-                !self.only_changes()
-                    || status_result
+            },
+            |(project, status)| {
+                progress::clear_line();
+                let include = !self.only_changes()
+                    || status
                         .as_ref()
-                        .map(|status| {
-                            status.iter().any(|b| !b.is_clean())
-                                || status.iter().all(|b| b.upstream_name.is_none())
+                        .map(|report| {
+                            report.has_changes()
+                                || !report.status().head.is_clean()
+                                || report.status().iter().any(|b| !b.is_clean())
+                                || report.status().iter().all(|b| b.upstream_name.is_none())
                         })
-                        .unwrap_or(false)
-            })
-            .map(|(project, status)| {
-                if let Some(p) = palette {
-                    print_status(project, &status, p);
+                        .unwrap_or(false);
+
+                if include {
+                    if let Some(p) = palette {
+                        print_status(project, &status, p);
+                    }
+                    report.insert(project, status);
                 }
-                (project, status)
-            })
-            .collect()
+            },
+        );
+        progress::clear_line();
+
+        report
     }
 
     fn make_report<'ws>(
         &self,
         working_dir: &Path,
         workspace: &'ws Workspace,
-    ) -> WorkspaceStatus<'ws> {
+    ) -> WorkspaceStatus<'ws, Self::Report> {
         self.make_report_and_maybe_print(working_dir, workspace, None)
     }
 
@@ -114,7 +173,77 @@ pub trait RepositoryCommand {
         &self,
         project: &Project,
         repository: &git2::Repository,
-    ) -> Result<RepositoryStatus, Error>;
+    ) -> Result<Self::Report, Error>;
+}
+
+/// A `RepositoryCommand` with its report type erased, so that commands with
+/// different reports can be run from the same place.
+pub trait AnyRepositoryCommand {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error>;
+
+    fn run_serialized(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        format: SerializedFormat,
+    ) -> Result<i32, Error>;
+}
+
+impl<C: RepositoryCommand> AnyRepositoryCommand for C {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        RepositoryCommand::run(self, working_dir, workspace, palette)
+    }
+
+    fn run_serialized(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        format: SerializedFormat,
+    ) -> Result<i32, Error> {
+        RepositoryCommand::run_serialized(self, working_dir, workspace, format)
+    }
+}
+
+/// Print an error for each path argument that matches no project in the
+/// workspace. Returns the exit code to fail with, if any.
+pub fn check_path_args(filter: &ProjectFilter, workspace: &Workspace) -> Option<i32> {
+    let unmatched = filter.unmatched_paths(&workspace.projects);
+    for arg in &unmatched {
+        eprintln!("No project matches path: {}", arg);
+    }
+    if unmatched.is_empty() {
+        None
+    } else {
+        Some(exit_codes::USER_ERROR)
+    }
+}
+
+fn report_exit_code<R: RepositoryReport>(reports: &WorkspaceStatus<R>) -> i32 {
+    reports
+        .values()
+        .map(|project_result| match project_result {
+            Ok(report) if report.failed() => exit_codes::INTERNAL_ERROR,
+            Ok(_) => exit_codes::OK,
+            Err(Error::RepositoryMissing) => exit_codes::OK,
+            Err(_) => exit_codes::INTERNAL_ERROR,
+        })
+        .fold(exit_codes::OK, |exit_code, next_code| {
+            if next_code != exit_codes::OK {
+                next_code
+            } else {
+                exit_code
+            }
+        })
 }
 
 fn ellipsisize(s: &str, length: usize) -> String {
@@ -141,7 +270,22 @@ pub fn format_message_line(message: &str) -> String {
 }
 
 pub fn format_project_header(project: &Project, palette: &Palette) -> String {
-    format!("{}:", palette.repo.paint(project.path.clone()))
+    format_path_header(&project.path, palette)
+}
+
+pub fn format_path_header(path: &str, palette: &Palette) -> String {
+    format!("{}:", palette.repo.paint(path.to_string()))
+}
+
+fn format_ahead_behind(ahead: usize, behind: usize) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if ahead > 0 {
+        parts.push(format!("↑{}", ahead));
+    }
+    if behind > 0 {
+        parts.push(format!("↓{}", behind));
+    }
+    parts.join(" ")
 }
 
 fn describe_sync_status<'a>(status: &'a BranchStatus, palette: &Palette) -> ANSIString<'a> {
@@ -149,17 +293,23 @@ fn describe_sync_status<'a>(status: &'a BranchStatus, palette: &Palette) -> ANSI
         Some(upstream_name) => {
             if status.fast_forwarded {
                 palette.cloning.paint("Fast-forwarded")
+            } else if status.rebased {
+                palette
+                    .cloning
+                    .paint(format!("Rebased onto {}", upstream_name))
             } else if status.upstream_fetched {
                 palette.cloning.paint("New upstream commits")
             } else {
                 match status.in_sync {
                     Some(true) => palette.clean.paint("Clean".to_string()),
-                    Some(false) => palette
-                        .dirty
-                        .paint(format!("Not in sync with {}", upstream_name)),
+                    Some(false) => palette.dirty.paint(format!(
+                        "Not in sync with {} {}",
+                        upstream_name,
+                        format_ahead_behind(status.ahead, status.behind)
+                    )),
                     None => palette
                         .missing
-                        .paint(format!("No remote branch {}", upstream_name)),
+                        .paint(format!("Upstream {} is gone", upstream_name)),
                 }
             }
         }
@@ -167,34 +317,85 @@ fn describe_sync_status<'a>(status: &'a BranchStatus, palette: &Palette) -> ANSI
     }
 }
 
+pub fn describe_dirty_state<'a>(dirty: &DirtyState, palette: &Palette) -> ANSIString<'a> {
+    match dirty {
+        DirtyState::Clean => palette.clean.paint("Clean"),
+        DirtyState::Conflicts => palette.conflict.paint("Dirty (Conflicts)"),
+        DirtyState::StagedChanges => palette.staged.paint("Dirty (Staged changes)"),
+        DirtyState::StagedAndUnstagedChanges => {
+            palette.dirty.paint("Dirty (Staged and unstaged changes)")
+        }
+        DirtyState::UnstagedChanges => palette.dirty.paint("Dirty (Unstaged changes)"),
+        DirtyState::UntrackedFiles => palette.dirty.paint("Dirty (Untracked files)"),
+    }
+}
+
+pub fn describe_operation<'a>(operation: OngoingOperation, palette: &Palette) -> ANSIString<'a> {
+    palette.in_progress.paint(match operation {
+        OngoingOperation::ApplyMailbox => "Applying patches",
+        OngoingOperation::Bisect => "Bisecting",
+        OngoingOperation::CherryPick => "Cherry-picking",
+        OngoingOperation::Merge => "Merging",
+        OngoingOperation::Rebase => "Rebasing",
+        OngoingOperation::Revert => "Reverting",
+    })
+}
+
+pub fn describe_stashes<'a>(stashes: usize, palette: &Palette) -> ANSIString<'a> {
+    palette.stashed.paint(if stashes == 1 {
+        "1 stash".to_string()
+    } else {
+        format!("{} stashes", stashes)
+    })
+}
+
 fn describe_status(status: &BranchStatus, palette: &Palette) -> String {
     if status.is_head {
-        let fetch_prefix = if status.upstream_fetched {
-            format!("{} - ", palette.cloning.paint("New upstream commits"))
-        } else {
-            "".to_string()
-        };
+        let mut parts: Vec<String> = Vec::new();
+
+        if let Some(operation) = status.operation {
+            parts.push(describe_operation(operation, palette).to_string());
+        }
 
         match status.dirty {
-            DirtyState::Clean => describe_sync_status(status, palette).to_string(),
-            DirtyState::UncommittedChanges => format!(
-                "{}{}",
-                fetch_prefix,
-                palette
-                    .dirty
-                    .paint("Dirty (Uncommitted changes)".to_string()),
-            ),
-            DirtyState::UntrackedFiles => format!(
-                "{}{}",
-                fetch_prefix,
-                palette.dirty.paint("Dirty (Untracked files)".to_string()),
-            ),
+            DirtyState::Clean => parts.push(describe_sync_status(status, palette).to_string()),
+            _ => {
+                if status.upstream_fetched {
+                    parts.push(palette.cloning.paint("New upstream commits").to_string());
+                }
+                parts.push(describe_dirty_state(&status.dirty, palette).to_string());
+            }
+        }
+
+        if status.stashes > 0 {
+            parts.push(describe_stashes(status.stashes, palette).to_string());
         }
+
+        parts.join(" - ")
     } else {
         describe_sync_status(status, palette).to_string()
     }
 }
 
+fn describe_detached_head(head: &DetachedHead, palette: &Palette) -> String {
+    let mut parts: Vec<String> = Vec::new();
+
+    if let Some(operation) = head.operation {
+        parts.push(describe_operation(operation, palette).to_string());
+    }
+    parts.push(describe_dirty_state(&head.dirty, palette).to_string());
+    if head.stashes > 0 {
+        parts.push(describe_stashes(head.stashes, palette).to_string());
+    }
+
+    format_branch_line(
+        palette,
+        true,
+        &format!("(detached at {})", &head.commit[..7]),
+        &parts.join(" - "),
+    )
+}
+
 fn describe_full(status: &BranchStatus, palette: &Palette) -> String {
     format_branch_line(
         palette,
@@ -204,18 +405,23 @@ fn describe_full(status: &BranchStatus, palette: &Palette) -> String {
     )
 }
 
-pub fn print_status(
+pub fn print_status<R: RepositoryReport>(
     project: &Project,
-    project_status: &Result<RepositoryStatus, Error>,
+    project_status: &Result<R, Error>,
     palette: &Palette,
 ) {
     println!("{}", format_project_header(project, palette));
 
     match project_status {
-        Ok(status) => {
+        Ok(report) => {
+            let status = report.status();
             for b in status {
                 println!("{}", describe_full(b, palette));
             }
+            if let Head::Detached(head) = &status.head {
+                println!("{}", describe_detached_head(head, palette));
+            }
+            report.print_results(palette);
         }
         Err(Error::RepositoryMissing) => {
             println!(
@@ -241,35 +447,117 @@ pub fn print_status(
     }
 }
 
-pub fn get_repobuilder<'a>() -> git2::build::RepoBuilder<'a> {
-    use git2::CredentialType;
+/// The remote-tracking branch for the default branch of the project's main
+/// remote: the project's `default_branch` if set, otherwise the branch that
+/// the remote's `HEAD` points to.
+pub fn default_remote_branch<'repo>(
+    repo: &'repo git2::Repository,
+    project: &Project,
+) -> Result<git2::Branch<'repo>, String> {
+    let remote = &project.main_remote.name;
+    let name = match &project.default_branch {
+        Some(branch) => format!("{}/{}", remote, branch),
+        None => repo
+            .find_reference(&format!("refs/remotes/{}/HEAD", remote))
+            .ok()
+            .and_then(|head| head.symbolic_target().map(str::to_string))
+            .and_then(|target| target.strip_prefix("refs/remotes/").map(str::to_string))
+            .ok_or_else(|| format!("Default branch of {} not known", remote))?,
+    };
+    repo.find_branch(&name, git2::BranchType::Remote)
+        .map_err(|_| format!("Branch {} not found", name))
+}
+
+/// The git config key in which `fetch` records the last target of a pruned
+/// upstream, for `prune-branches` to check whether the branch was merged into
+/// it.
+fn former_upstream_key(branch: &str) -> String {
+    format!("branch.{}.gwsFormerUpstream", branch)
+}
+
+/// Record `target` as the former upstream of the local branches whose upstream
+/// is the pruned remote-tracking branch `upstream_name`, like `origin/foo`.
+pub fn record_former_upstream(
+    repo: &git2::Repository,
+    upstream_name: &str,
+    target: git2::Oid,
+) -> Result<(), Error> {
+    let upstream_ref = format!("refs/remotes/{}", upstream_name);
+    let mut config = repo.config()?;
+    for branch in repo.branches(Some(git2::BranchType::Local))? {
+        let (branch, _) = branch?;
+        let tracks_upstream = branch
+            .get()
+            .name()
+            .and_then(|name| repo.branch_upstream_name(name).ok())
+            .map(|name| name.as_str() == Some(&upstream_ref))
+            .unwrap_or(false);
+        if tracks_upstream {
+            let name = branch.name()?.ok_or(Error::NoBranchNameFound)?;
+            config.set_str(&former_upstream_key(name), &target.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// The last target of the pruned upstream of `branch`, if `fetch` recorded it.
+pub fn former_upstream(repo: &git2::Repository, branch: &str) -> Option<git2::Oid> {
+    let target = repo
+        .config()
+        .ok()?
+        .get_string(&former_upstream_key(branch))
+        .ok()?;
+    git2::Oid::from_str(&target).ok()
+}
 
+/// Find all git repositories under `working_dir`, returned as paths relative to
+/// `working_dir` with `/` as separator.
+///
+/// Only directories containing a `.git` directory count as repositories, so
+/// submodules (which have a `.git` file) are not included. The search does not
+/// descend into repositories or hidden directories, and does not follow
+/// symlinks.
+pub fn find_repositories(working_dir: &Path) -> Result<BTreeSet<String>, Error> {
+    fn visit(root: &Path, dir: &Path, found: &mut BTreeSet<String>) -> Result<(), std::io::Error> {
+        if dir != root && dir.join(".git").is_dir() {
+            if let Ok(relative) = dir.strip_prefix(root) {
+                let segments: Vec<&str> = relative
+                    .components()
+                    .flat_map(|c| c.as_os_str().to_str())
+                    .collect();
+                found.insert(segments.join("/"));
+            }
+            return Ok(());
+        }
+
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if entry.file_type()?.is_dir() && !hidden {
+                visit(root, &entry.path(), found)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut found = BTreeSet::new();
+    visit(working_dir, working_dir, &mut found)?;
+    Ok(found)
+}
+
+/// A `RepoBuilder` with callbacks for `credentials`, and progress callbacks
+/// that label the progress line with `progress_label` when cloning one of
+/// `jobs` concurrent projects.
+pub fn get_repobuilder<'a>(
+    credentials: &Credentials,
+    progress_label: &str,
+    jobs: usize,
+) -> git2::build::RepoBuilder<'a> {
     let mut result = git2::build::RepoBuilder::new();
     let mut fopts = git2::FetchOptions::new();
-    let mut callbacks = git2::RemoteCallbacks::new();
-    let mut tried_ssh = false;
-    let mut tried_password = false;
-
-    callbacks.credentials(
-        move |url: &str, username: Option<&str>, allowed: CredentialType| {
-            let mut cred_helper = git2::CredentialHelper::new(url);
-            cred_helper.config(&git2::Config::open_default()?);
-
-            if allowed.contains(CredentialType::SSH_KEY) && !tried_ssh {
-                tried_ssh = true;
-                let user: String = username
-                    .map(str::to_string)
-                    .or_else(|| cred_helper.username.clone())
-                    .unwrap_or_else(|| "git".to_string());
-                git2::Cred::ssh_key_from_agent(&user)
-            } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && !tried_password {
-                tried_password = true;
-                git2::Cred::credential_helper(&git2::Config::open_default()?, url, username)
-            } else {
-                git2::Cred::default()
-            }
-        },
-    );
+    let mut callbacks = credentials.remote_callbacks();
+    progress::add_progress_callbacks(&mut callbacks, progress_label, jobs);
     fopts.remote_callbacks(callbacks);
     result.fetch_options(fopts);
     result
@@ -302,22 +590,43 @@ pub fn get_repobuilder<'a>() -> git2::build::RepoBuilder<'a> {
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
-pub fn update_submodules(repo: &git2::Repository) -> Result<(), git2::Error> {
+pub fn update_submodules(
+    repo: &git2::Repository,
+    credentials: &Credentials,
+) -> Result<(), git2::Error> {
     fn add_subrepos(
         repo: &git2::Repository,
         list: &mut Vec<git2::Repository>,
+        credentials: &Credentials,
     ) -> Result<(), git2::Error> {
         for mut subm in repo.submodules()? {
-            subm.update(true, None)?;
+            let mut fetch_options = git2::FetchOptions::new();
+            fetch_options.remote_callbacks(credentials.remote_callbacks());
+            let mut update_options = git2::SubmoduleUpdateOptions::new();
+            update_options.fetch(fetch_options);
+            subm.update(true, Some(&mut update_options))?;
             list.push(subm.open()?);
         }
         Ok(())
     }
 
     let mut repos = Vec::new();
-    add_subrepos(repo, &mut repos)?;
+    add_subrepos(repo, &mut repos, credentials)?;
     while let Some(repo) = repos.pop() {
-        add_subrepos(&repo, &mut repos)?;
+        add_subrepos(&repo, &mut repos, credentials)?;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::format_ahead_behind;
+
+    #[test]
+    fn ahead_behind_omits_zero_counts() {
+        assert_eq!(format_ahead_behind(3, 5), "↑3 ↓5");
+        assert_eq!(format_ahead_behind(3, 0), "↑3");
+        assert_eq!(format_ahead_behind(0, 5), "↓5");
+        assert_eq!(format_ahead_behind(0, 0), "");
+    }
+}
@@ -0,0 +1,146 @@
+use std::cell::Cell;
+use std::io::IsTerminal;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Minimum time between redraws of a progress line.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+fn on_terminal() -> bool {
+    std::io::stdout().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Whether progress lines are drawn when processing `jobs` projects at a time.
+/// They are only drawn when output goes to a terminal, so that redirected
+/// output stays free of control characters, and only for one project at a
+/// time, since concurrent projects would overwrite each other's line.
+pub fn enabled(jobs: usize) -> bool {
+    draws_progress(on_terminal(), jobs)
+}
+
+fn draws_progress(on_terminal: bool, jobs: usize) -> bool {
+    on_terminal && jobs <= 1
+}
+
+/// Erase the progress line, if any, so that regular output can be printed.
+pub fn clear_line() {
+    if on_terminal() {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+fn draw_line(line: &str) {
+    eprint!("\r\x1b[2K{}", line);
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Describe a transfer that has been running for `elapsed`.
+pub fn format_transfer(label: &str, stats: &git2::Progress, elapsed: Duration) -> String {
+    let received_bytes = stats.received_bytes();
+    let rate = if elapsed.as_secs_f64() > 0.0 {
+        (received_bytes as f64 / elapsed.as_secs_f64()) as usize
+    } else {
+        0
+    };
+
+    if stats.received_objects() == stats.total_objects() && stats.total_deltas() > 0 {
+        format!(
+            "{}: resolving deltas {}/{}",
+            label,
+            stats.indexed_deltas(),
+            stats.total_deltas()
+        )
+    } else {
+        format!(
+            "{}: {}/{} objects, {} ({}/s)",
+            label,
+            stats.received_objects(),
+            stats.total_objects(),
+            format_bytes(received_bytes),
+            format_bytes(rate)
+        )
+    }
+}
+
+/// Add callbacks to `callbacks` that draw a progress line prefixed with
+/// `label` while a fetch or clone is running. Does nothing if progress lines
+/// are not `enabled` for `jobs`.
+pub fn add_progress_callbacks(callbacks: &mut git2::RemoteCallbacks, label: &str, jobs: usize) {
+    if !enabled(jobs) {
+        return;
+    }
+
+    let started = Instant::now();
+    let last_drawn: Rc<Cell<Option<Instant>>> = Rc::new(Cell::new(None));
+    let should_draw = move || {
+        let now = Instant::now();
+        match last_drawn.get() {
+            Some(t) if now - t < REDRAW_INTERVAL => false,
+            _ => {
+                last_drawn.set(Some(now));
+                true
+            }
+        }
+    };
+    let should_draw_transfer = should_draw.clone();
+
+    let transfer_label = label.to_string();
+    callbacks.transfer_progress(move |stats| {
+        if should_draw_transfer() {
+            draw_line(&format_transfer(&transfer_label, &stats, started.elapsed()));
+        }
+        true
+    });
+
+    let sideband_label = label.to_string();
+    callbacks.sideband_progress(move |data| {
+        if should_draw() {
+            if let Some(message) = String::from_utf8_lossy(data)
+                .split(['\r', '\n'])
+                .map(str::trim)
+                .rfind(|s| !s.is_empty())
+            {
+                draw_line(&format!("{}: remote: {}", sideband_label, message));
+            }
+        }
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::draws_progress;
+    use super::format_bytes;
+
+    #[test]
+    fn progress_is_drawn_only_on_terminal_for_one_job() {
+        assert!(draws_progress(true, 1));
+        assert!(!draws_progress(false, 1));
+        assert!(!draws_progress(true, 2));
+        assert!(!draws_progress(false, 4));
+    }
+
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use super::common::exit_codes;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Workspace;
+use crate::config::parse::toml_workspace;
+use crate::config::read::read_workspace_file;
+
+pub struct Convert {}
+
+impl DirectoryCommand for Convert {
+    fn run(&self, working_dir: &Path, _: &Workspace, palette: &Palette) -> Result<i32, Error> {
+        let legacy_file_path = working_dir.join(".projects.gws");
+        let toml_file_path = working_dir.join(".projects.toml");
+
+        if toml_file_path.exists() {
+            eprintln!("Projects file already exists: {:?}", toml_file_path);
+            return Ok(exit_codes::USER_ERROR);
+        }
+
+        // Re-read the legacy file rather than using the given workspace, since
+        // that has already been filtered by .ignore.gws.
+        let workspace = match read_workspace_file(&legacy_file_path) {
+            Ok(workspace) => workspace,
+            Err(err) => {
+                eprintln!(
+                    "Failed to read projects file {:?}: {:?}",
+                    legacy_file_path, err
+                );
+                return Ok(exit_codes::USER_ERROR);
+            }
+        };
+
+        std::fs::write(&toml_file_path, toml_workspace::serialize(&workspace))?;
+
+        println!(
+            "{}",
+            palette.clean.paint(format!(
+                "Converted {} projects to {:?}. {:?} is no longer used and can be removed.",
+                workspace.projects.len(),
+                toml_file_path,
+                legacy_file_path
+            ))
+        );
+
+        Ok(exit_codes::OK)
+    }
+}
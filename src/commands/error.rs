@@ -1,24 +1,63 @@
 use std::fmt;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub enum Error {
     Git2(git2::Error),
+    Io(std::io::Error),
     NoBranchNameFound,
     RepositoryMissing,
 }
 
+impl PartialEq for Error {
+    fn eq(&self, rhs: &Self) -> bool {
+        match (self, rhs) {
+            (Error::Git2(a), Error::Git2(b)) => a == b,
+            (Error::Io(a), Error::Io(b)) => a.kind() == b.kind(),
+            (Error::NoBranchNameFound, Error::NoBranchNameFound) => true,
+            (Error::RepositoryMissing, Error::RepositoryMissing) => true,
+            (_, _) => false,
+        }
+    }
+}
+
 impl From<git2::Error> for Error {
     fn from(e: git2::Error) -> Error {
         Error::Git2(e)
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
             Error::Git2(e) => fmt::Display::fmt(e, formatter),
+            Error::Io(e) => fmt::Display::fmt(e, formatter),
             Error::NoBranchNameFound => write!(formatter, "No branch name found"),
             Error::RepositoryMissing => write!(formatter, "Repository missing"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+
+    use super::Error;
+
+    #[test]
+    fn io_errors_are_equal_if_their_kinds_are() {
+        assert_eq!(
+            Error::Io(std::io::Error::new(ErrorKind::NotFound, "foo")),
+            Error::Io(std::io::Error::new(ErrorKind::NotFound, "bar"))
+        );
+        assert_ne!(
+            Error::Io(std::io::Error::new(ErrorKind::NotFound, "foo")),
+            Error::Io(std::io::Error::new(ErrorKind::PermissionDenied, "foo"))
+        );
+    }
+}
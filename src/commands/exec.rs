@@ -0,0 +1,183 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::Output;
+use std::process::Stdio;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use super::common::check_path_args;
+use super::common::exit_codes;
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
+use super::common::format_project_header;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Project;
+use crate::config::data::Workspace;
+use crate::util::parallel::for_each_in_order;
+
+pub struct Exec {
+    pub projects: ProjectFilter,
+    pub jobs: usize,
+    /// Don't start the command in any more projects after it fails in one.
+    pub fail_fast: bool,
+    /// The program to run, followed by its arguments.
+    pub command: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ExecOutcome {
+    Missing,
+    /// Not run because the command failed in another project and
+    /// `fail_fast` is set.
+    Skipped,
+    /// The command ran, and this is its captured output and exit status.
+    Finished(Output),
+    /// The command could not be started.
+    Error(std::io::Error),
+}
+
+impl ExecOutcome {
+    fn failed(&self) -> bool {
+        match self {
+            ExecOutcome::Missing | ExecOutcome::Skipped => false,
+            ExecOutcome::Finished(output) => !output.status.success(),
+            ExecOutcome::Error(_) => true,
+        }
+    }
+
+    fn exit_code(&self) -> i32 {
+        match self {
+            ExecOutcome::Missing | ExecOutcome::Skipped => exit_codes::OK,
+            ExecOutcome::Finished(output) if output.status.success() => exit_codes::OK,
+            ExecOutcome::Finished(_) => exit_codes::UNKNOWN_ERROR,
+            ExecOutcome::Error(_) => exit_codes::USER_ERROR,
+        }
+    }
+}
+
+fn print_outcome(project: &Project, outcome: &ExecOutcome, palette: &Palette) {
+    println!("{}", format_project_header(project, palette));
+    match outcome {
+        ExecOutcome::Missing => println!(
+            "{}",
+            palette
+                .missing
+                .paint(format_message_line("Missing repository"))
+        ),
+        ExecOutcome::Skipped => println!(
+            "{}",
+            palette
+                .missing
+                .paint(format_message_line("Skipped after earlier failure"))
+        ),
+        ExecOutcome::Finished(output) => {
+            // Ignore write errors, like println! would panic on instead
+            std::io::stdout().write_all(&output.stdout).ok();
+            std::io::stderr().write_all(&output.stderr).ok();
+            if !output.status.success() {
+                let message = match output.status.code() {
+                    Some(code) => format!("Exited with status {}", code),
+                    None => "Terminated by signal".to_string(),
+                };
+                println!("{}", palette.error.paint(format_message_line(&message)));
+            }
+        }
+        ExecOutcome::Error(err) => println!(
+            "{}",
+            palette.error.paint(format_message_line(&format!(
+                "Failed to run command: {}",
+                err
+            )))
+        ),
+    }
+}
+
+/// The first nonzero exit code among `report`, or `OK` if there is none.
+pub fn report_exit_code(report: &[(&Project, ExecOutcome)]) -> i32 {
+    report
+        .iter()
+        .map(|(_, outcome)| outcome.exit_code())
+        .find(|exit_code| *exit_code != exit_codes::OK)
+        .unwrap_or(exit_codes::OK)
+}
+
+impl Exec {
+    fn run_in(&self, project_dir: &Path) -> ExecOutcome {
+        if !project_dir.exists() {
+            return ExecOutcome::Missing;
+        }
+        let (program, args) = self
+            .command
+            .split_first()
+            .expect("Command should have at least one element");
+        match std::process::Command::new(program)
+            .args(args)
+            .current_dir(project_dir)
+            .stdin(Stdio::null())
+            .output()
+        {
+            Ok(output) => ExecOutcome::Finished(output),
+            Err(err) => ExecOutcome::Error(err),
+        }
+    }
+
+    /// Run the command in each selected project, and print the output of each
+    /// under a project header if `palette` is given. The output is grouped by
+    /// project and printed in workspace order, also when running in parallel.
+    pub fn make_report_and_maybe_print<'ws>(
+        &self,
+        working_dir: &Path,
+        workspace: &'ws Workspace,
+        palette: Option<&Palette>,
+    ) -> Vec<(&'ws Project, ExecOutcome)> {
+        let projects: Vec<&'ws Project> = workspace
+            .projects
+            .iter()
+            .filter(|project| self.projects.matches(project))
+            .collect();
+
+        let failed = AtomicBool::new(false);
+        let mut report = Vec::new();
+
+        for_each_in_order(
+            projects,
+            self.jobs,
+            |project| {
+                let outcome = if self.fail_fast && failed.load(Ordering::SeqCst) {
+                    ExecOutcome::Skipped
+                } else {
+                    self.run_in(&working_dir.join(&project.path))
+                };
+                if outcome.failed() {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                (*project, outcome)
+            },
+            |(project, outcome)| {
+                if let Some(p) = palette {
+                    print_outcome(project, &outcome, p);
+                }
+                report.push((project, outcome));
+            },
+        );
+
+        report
+    }
+}
+
+impl DirectoryCommand for Exec {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        if let Some(exit_code) = check_path_args(&self.projects, workspace) {
+            return Ok(exit_code);
+        }
+        let report = self.make_report_and_maybe_print(working_dir, workspace, Some(palette));
+        Ok(report_exit_code(&report))
+    }
+}
@@ -1,39 +1,156 @@
+use std::collections::BTreeMap;
 use std::collections::BTreeSet;
-use std::collections::HashSet;
 
+use serde::Serialize;
+
+use super::common::check_path_args;
+use super::common::credentials::Credentials;
+use super::common::exit_codes;
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
+use super::common::progress;
+use super::common::record_former_upstream;
 use super::common::RepositoryCommand;
+use super::common::RepositoryReport;
 use super::error::Error;
 use super::status::Status;
+use crate::color::palette::Palette;
 use crate::config::data::Project;
+use crate::config::data::Workspace;
+use crate::data::status::FetchError;
+use crate::data::status::FetchErrorKind;
 use crate::data::status::RepositoryStatus;
 
 pub struct Fetch {
     pub status_command: Status,
+    pub jobs: usize,
+    pub credentials: Credentials,
+    /// Whether to remove remote-tracking branches that no longer exist on the
+    /// remote. If `None`, git's `remote.<name>.prune` and `fetch.prune`
+    /// settings decide.
+    pub prune: Option<bool>,
+    /// Whether to fetch all tags (`Some(true)`) or none (`Some(false)`). If
+    /// `None`, the remote's `tagOpt` setting decides, and by default only tags
+    /// pointing into the fetched history are fetched.
+    pub tags: Option<bool>,
+    /// Only fetch the remote with this name, in the projects that have it. At
+    /// least one of the selected projects must have it.
+    pub remote: Option<String>,
+}
+
+/// The status of a project after fetching it, the remotes that failed to
+/// fetch and the remote-tracking branches that were pruned.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct FetchReport {
+    #[serde(flatten)]
+    pub status: RepositoryStatus,
+    /// Remotes that failed to fetch, in the order they were fetched
+    pub fetch_errors: Vec<FetchError>,
+    /// Remote-tracking branches removed by a pruning fetch, like `origin/foo`
+    pub pruned_refs: Vec<String>,
+}
+
+impl RepositoryReport for FetchReport {
+    fn status(&self) -> &RepositoryStatus {
+        &self.status
+    }
+
+    fn has_changes(&self) -> bool {
+        !self.fetch_errors.is_empty() || !self.pruned_refs.is_empty()
+    }
+
+    fn failed(&self) -> bool {
+        !self.fetch_errors.is_empty()
+    }
+
+    fn print_results(&self, palette: &Palette) {
+        for pruned_ref in &self.pruned_refs {
+            println!(
+                "{}",
+                palette
+                    .cloning
+                    .paint(format_message_line(&format!("Pruned {}", pruned_ref)))
+            );
+        }
+        for fetch_error in &self.fetch_errors {
+            println!(
+                "{}",
+                palette.error.paint(format_message_line(&format!(
+                    "Failed to fetch {}: {}",
+                    fetch_error.remote, fetch_error.message
+                )))
+            );
+        }
+    }
 }
 
 struct FetchedProject {
     pub updated_branch_names: BTreeSet<String>,
+    pub fetch_errors: Vec<FetchError>,
+    pub pruned_refs: Vec<String>,
 }
 
+/// The remote-tracking branches of `remote` by shorthand name, like
+/// `origin/master`, and their targets.
+fn remote_tracking_refs(
+    repo: &git2::Repository,
+    remote_name: &str,
+) -> Result<BTreeMap<String, git2::Oid>, Error> {
+    let mut refs = BTreeMap::new();
+    for reference in repo.references_glob(&format!("refs/remotes/{}/*", remote_name))? {
+        let reference = reference?;
+        if let (Some(name), Some(target)) = (reference.shorthand(), reference.target()) {
+            refs.insert(name.to_string(), target);
+        }
+    }
+    Ok(refs)
+}
+
+/// Fetch `remote`, and return the names of the branches whose upstreams were
+/// updated and the remote-tracking branches that were pruned.
 fn do_fetch_remote<'repo>(
     project: &Project,
     repo: &'repo git2::Repository,
     remote: &mut git2::Remote,
-) -> Result<BTreeSet<String>, Error> {
+    fetch: &Fetch,
+) -> Result<(BTreeSet<String>, Vec<String>), Error> {
+    let remote_name = remote.name().unwrap_or("?").to_string();
     let heads_before = project.current_upstream_heads(repo)?;
+    let refs_before = remote_tracking_refs(repo, &remote_name)?;
 
     let refspec_strings: Vec<String> = remote
         .refspecs()
         .flat_map(|rs| rs.str().map(String::from))
         .collect();
 
+    let mut callbacks = fetch.credentials.remote_callbacks();
+    progress::add_progress_callbacks(
+        &mut callbacks,
+        &format!("{} ({})", project.path, remote_name),
+        fetch.jobs,
+    );
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options
+        .remote_callbacks(callbacks)
+        .prune(match fetch.prune {
+            Some(true) => git2::FetchPrune::On,
+            Some(false) => git2::FetchPrune::Off,
+            None => git2::FetchPrune::Unspecified,
+        })
+        .download_tags(match fetch.tags {
+            Some(true) => git2::AutotagOption::All,
+            Some(false) => git2::AutotagOption::None,
+            None => git2::AutotagOption::Unspecified,
+        });
+
     remote.fetch(
         &refspec_strings.iter().map(|s| &**s).collect::<Vec<&str>>(),
-        None,
+        Some(&mut fetch_options),
         None,
     )?;
 
     let heads_after = project.current_upstream_heads(repo)?;
+    let refs_after = remote_tracking_refs(repo, &remote_name)?;
 
     let updated_branches: BTreeSet<String> = heads_after
         .into_iter()
@@ -46,61 +163,138 @@ fn do_fetch_remote<'repo>(
         .map(|(k, _)| k)
         .collect();
 
-    Ok(updated_branches)
+    let mut pruned_refs = Vec::new();
+    for (name, target) in refs_before {
+        if !refs_after.contains_key(&name) {
+            record_former_upstream(repo, &name, target)?;
+            pruned_refs.push(name);
+        }
+    }
+
+    Ok((updated_branches, pruned_refs))
 }
 
-fn do_fetch(project: &Project, repo: &git2::Repository) -> FetchedProject {
-    FetchedProject {
-        updated_branch_names: project
-            .remotes()
-            .into_iter()
-            .flat_map(
-                |remote_config| match repo.find_remote(&remote_config.name) {
-                    Ok(mut remote) => do_fetch_remote(project, repo, &mut remote)
-                        .unwrap_or_default()
-                        .into_iter(),
-                    Err(_) => BTreeSet::new().into_iter(),
-                },
-            )
-            .collect(),
+fn fetch_error(remote_name: &str, error: &Error) -> FetchError {
+    match error {
+        Error::Git2(err) => FetchError::from_git2(remote_name, err),
+        err => FetchError {
+            remote: remote_name.to_string(),
+            kind: FetchErrorKind::Other,
+            message: err.to_string(),
+        },
     }
 }
 
+fn do_fetch(project: &Project, repo: &git2::Repository, fetch: &Fetch) -> FetchedProject {
+    let mut result = FetchedProject {
+        updated_branch_names: BTreeSet::new(),
+        fetch_errors: Vec::new(),
+        pruned_refs: Vec::new(),
+    };
+
+    for remote_config in project.remotes().into_iter().filter(|remote_config| {
+        fetch
+            .remote
+            .as_ref()
+            .map(|name| &remote_config.name == name)
+            .unwrap_or(true)
+    }) {
+        match repo.find_remote(&remote_config.name) {
+            Ok(mut remote) => match do_fetch_remote(project, repo, &mut remote, fetch) {
+                Ok((updated, pruned)) => {
+                    result.updated_branch_names.extend(updated);
+                    result.pruned_refs.extend(pruned);
+                }
+                Err(err) => result
+                    .fetch_errors
+                    .push(fetch_error(&remote_config.name, &err)),
+            },
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                result.fetch_errors.push(FetchError {
+                    remote: remote_config.name.clone(),
+                    kind: FetchErrorKind::MissingRemote,
+                    message: format!("Remote {} not found in repository", remote_config.name),
+                });
+            }
+            Err(err) => result
+                .fetch_errors
+                .push(FetchError::from_git2(&remote_config.name, &err)),
+        }
+    }
+
+    result
+}
+
 fn augment_project_status_report(
     status: RepositoryStatus,
     result: FetchedProject,
-) -> Result<RepositoryStatus, Error> {
+) -> Result<FetchReport, Error> {
     let updated = result.updated_branch_names;
-    Ok(status
-        .into_iter()
-        .map(|mut branch_status| {
-            branch_status.upstream_fetched = updated
-                .iter()
-                .any(|upd_name| &branch_status.name == upd_name);
-            branch_status
-        })
-        .collect())
+    Ok(FetchReport {
+        status: RepositoryStatus {
+            branches: status
+                .branches
+                .into_iter()
+                .map(|mut branch_status| {
+                    branch_status.upstream_fetched = updated
+                        .iter()
+                        .any(|upd_name| &branch_status.name == upd_name);
+                    branch_status
+                })
+                .collect(),
+            ..status
+        },
+        fetch_errors: result.fetch_errors,
+        pruned_refs: result.pruned_refs,
+    })
 }
 
 impl RepositoryCommand for Fetch {
+    type Report = FetchReport;
+
     fn only_changes(&self) -> bool {
         self.status_command.only_changes()
     }
 
-    fn project_args(&self) -> &HashSet<String> {
-        self.status_command.project_args()
+    fn jobs(&self) -> usize {
+        self.jobs
+    }
+
+    fn project_filter(&self) -> &ProjectFilter {
+        self.status_command.project_filter()
+    }
+
+    fn check_args(&self, workspace: &Workspace) -> Option<i32> {
+        check_path_args(self.project_filter(), workspace).or_else(|| {
+            let remote = self.remote.as_ref()?;
+            let found = workspace
+                .projects
+                .iter()
+                .filter(|project| self.project_filter().matches(project))
+                .any(|project| project.remotes().iter().any(|r| &r.name == remote));
+            if found {
+                None
+            } else {
+                eprintln!("No project has remote: {}", remote);
+                Some(exit_codes::USER_ERROR)
+            }
+        })
     }
 
     fn run_project(
         &self,
         project: &Project,
         repository: &git2::Repository,
-    ) -> Result<RepositoryStatus, Error> {
-        self.status_command
-            .run_project(project, repository)
-            .and_then(|project_status| {
-                let fetch_result = do_fetch(project, repository);
-                augment_project_status_report(project_status, fetch_result)
-            })
+    ) -> Result<FetchReport, Error> {
+        let project_status = self.status_command.run_project(project, repository)?;
+        let fetch_result = do_fetch(project, repository, self);
+        // Pruning can leave branches with a gone upstream, which the status
+        // from before the fetch doesn't show.
+        let project_status = if fetch_result.pruned_refs.is_empty() {
+            project_status
+        } else {
+            self.status_command.run_project(project, repository)?
+        };
+        augment_project_status_report(project_status, fetch_result)
     }
 }
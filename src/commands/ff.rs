@@ -1,75 +1,255 @@
-use std::collections::HashSet;
+use serde::Serialize;
 
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
 use super::common::RepositoryCommand;
+use super::common::RepositoryReport;
 use super::error::Error;
 use super::fetch::Fetch;
+use super::fetch::FetchReport;
+use crate::color::palette::Palette;
 use crate::config::data::Project;
 use crate::crate_info::crate_name;
+use crate::data::status::BranchError;
 use crate::data::status::BranchMethods;
+use crate::data::status::BranchStatus;
 use crate::data::status::DirtyState;
 use crate::data::status::RepositoryStatus;
 
 pub struct FastForward {
     pub fetch_command: Fetch,
+    /// Rebase clean branches that have diverged from their upstreams.
+    pub rebase: bool,
 }
 
-fn do_ff(
+/// Point `branch` at `target`. If the branch is checked out, the working tree
+/// is first updated with a safe checkout, which fails instead of overwriting
+/// local changes, in which case the branch is not moved.
+fn move_branch(
     repo: &git2::Repository,
-    status_report: RepositoryStatus,
-) -> Result<RepositoryStatus, Error> {
-    status_report
-        .into_iter()
-        .map(|mut branch_status| {
-            if branch_status.dirty == DirtyState::Clean && branch_status.upstream_name.is_some() {
-                let branch = repo.find_branch(&branch_status.name, git2::BranchType::Local)?;
-                let branch_id = branch.get().peel_to_commit()?.id();
-                let upstream = branch.upstream()?;
-                let upstream_id: git2::Oid = upstream.get().peel_to_commit()?.id();
-
-                if branch_id != upstream_id {
-                    let can_fast_forward = repo.graph_descendant_of(upstream_id, branch_id)?;
-
-                    if can_fast_forward {
-                        let reflog_msg = format!(
-                            "{prog_name}: Fast-forward {branch_name} to upstream {upstream_name}",
-                            prog_name = crate_name(),
-                            branch_name = branch.branch_name()?,
-                            upstream_name = upstream.branch_name()?
-                        );
-
-                        branch
-                            .into_reference()
-                            .set_target(upstream_id, &reflog_msg)?;
-
-                        if branch_status.is_head {
-                            repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
-                        }
-
-                        branch_status.fast_forwarded = true;
+    branch: git2::Branch,
+    target: git2::Oid,
+    is_head: bool,
+    reflog_msg: &str,
+) -> Result<(), git2::Error> {
+    if is_head {
+        let tree = repo.find_commit(target)?.tree()?;
+        repo.checkout_tree(
+            tree.as_object(),
+            Some(git2::build::CheckoutBuilder::new().safe()),
+        )?;
+    }
+    branch.into_reference().set_target(target, reflog_msg)?;
+    Ok(())
+}
+
+/// Rebase the commits in `branch_id` but not in `upstream_id` onto
+/// `upstream_id`, without touching any refs or the working tree. Returns the
+/// rebased head commit, or `None` if there were conflicts.
+fn rebase_in_memory(
+    repo: &git2::Repository,
+    branch_id: git2::Oid,
+    upstream_id: git2::Oid,
+) -> Result<Option<git2::Oid>, git2::Error> {
+    let branch_commit = repo.find_annotated_commit(branch_id)?;
+    let upstream_commit = repo.find_annotated_commit(upstream_id)?;
+    let mut rebase = repo.rebase(
+        Some(&branch_commit),
+        Some(&upstream_commit),
+        None,
+        Some(git2::RebaseOptions::new().inmemory(true)),
+    )?;
+    let committer = repo.signature()?;
+
+    let mut head = upstream_id;
+    while let Some(operation) = rebase.next() {
+        if let Err(err) = operation {
+            rebase.abort()?;
+            return Err(err);
+        }
+        if rebase.inmemory_index()?.has_conflicts() {
+            rebase.abort()?;
+            return Ok(None);
+        }
+        match rebase.commit(None, &committer, None) {
+            Ok(id) => head = id,
+            // The change is already upstream, so the commit is dropped
+            Err(err) if err.code() == git2::ErrorCode::Applied => {}
+            Err(err) => {
+                rebase.abort()?;
+                return Err(err);
+            }
+        }
+    }
+    rebase.finish(None)?;
+
+    Ok(Some(head))
+}
+
+impl FastForward {
+    /// Fast-forward or rebase `branch_status` if possible. Failures that leave
+    /// the branch untouched are added to `errors`.
+    fn update_branch(
+        &self,
+        repo: &git2::Repository,
+        branch_status: &mut BranchStatus,
+        errors: &mut Vec<BranchError>,
+    ) -> Result<(), Error> {
+        if branch_status.dirty != DirtyState::Clean
+            || branch_status.operation.is_some()
+            || branch_status.upstream_name.is_none()
+            || branch_status.upstream_gone()
+        {
+            return Ok(());
+        }
+
+        let branch = repo.find_branch(&branch_status.name, git2::BranchType::Local)?;
+        let branch_id = branch.get().peel_to_commit()?.id();
+        let upstream = branch.upstream()?;
+        let upstream_id: git2::Oid = upstream.get().peel_to_commit()?.id();
+        let upstream_name = upstream.branch_name()?.to_string();
+
+        if branch_id == upstream_id {
+            return Ok(());
+        }
+
+        if repo.graph_descendant_of(upstream_id, branch_id)? {
+            let reflog_msg = format!(
+                "{prog_name}: Fast-forward {branch_name} to upstream {upstream_name}",
+                prog_name = crate_name(),
+                branch_name = branch.branch_name()?,
+                upstream_name = upstream_name
+            );
+
+            match move_branch(
+                repo,
+                branch,
+                upstream_id,
+                branch_status.is_head,
+                &reflog_msg,
+            ) {
+                Ok(()) => branch_status.fast_forwarded = true,
+                Err(err) => errors.push(BranchError {
+                    branch: branch_status.name.clone(),
+                    message: format!("Failed to fast-forward: {}", err.message()),
+                }),
+            }
+        } else if self.rebase && !repo.graph_descendant_of(branch_id, upstream_id)? {
+            let reflog_msg = format!(
+                "{prog_name}: Rebase {branch_name} onto upstream {upstream_name}",
+                prog_name = crate_name(),
+                branch_name = branch.branch_name()?,
+                upstream_name = upstream_name
+            );
+
+            let result =
+                rebase_in_memory(repo, branch_id, upstream_id).and_then(|rebased| match rebased {
+                    Some(rebased_id) => {
+                        move_branch(repo, branch, rebased_id, branch_status.is_head, &reflog_msg)
+                            .map(|()| true)
                     }
-                }
+                    None => Ok(false),
+                });
+
+            match result {
+                Ok(true) => branch_status.rebased = true,
+                Ok(false) => errors.push(BranchError {
+                    branch: branch_status.name.clone(),
+                    message: format!("Conflicts when rebasing onto {}, aborted", upstream_name),
+                }),
+                Err(err) => errors.push(BranchError {
+                    branch: branch_status.name.clone(),
+                    message: format!("Failed to rebase: {}", err.message()),
+                }),
             }
-            Ok(branch_status)
+        }
+
+        Ok(())
+    }
+
+    fn do_ff(&self, repo: &git2::Repository, fetch_report: FetchReport) -> Result<FfReport, Error> {
+        let mut ff_errors = Vec::new();
+        let branches = fetch_report
+            .status
+            .branches
+            .into_iter()
+            .map(|mut branch_status| {
+                self.update_branch(repo, &mut branch_status, &mut ff_errors)?;
+                Ok(branch_status)
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(FfReport {
+            fetch: FetchReport {
+                status: RepositoryStatus {
+                    branches,
+                    ..fetch_report.status
+                },
+                ..fetch_report
+            },
+            ff_errors,
         })
-        .collect()
+    }
+}
+
+/// The status of a project after fetching and fast-forwarding it, and the
+/// branches that could not be fast-forwarded or rebased.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct FfReport {
+    #[serde(flatten)]
+    pub fetch: FetchReport,
+    pub ff_errors: Vec<BranchError>,
+}
+
+impl RepositoryReport for FfReport {
+    fn status(&self) -> &RepositoryStatus {
+        self.fetch.status()
+    }
+
+    fn has_changes(&self) -> bool {
+        !self.ff_errors.is_empty() || self.fetch.has_changes()
+    }
+
+    fn failed(&self) -> bool {
+        !self.ff_errors.is_empty() || self.fetch.failed()
+    }
+
+    fn print_results(&self, palette: &Palette) {
+        for ff_error in &self.ff_errors {
+            println!(
+                "{}",
+                palette.error.paint(format_message_line(&format!(
+                    "{}: {}",
+                    ff_error.branch, ff_error.message
+                )))
+            );
+        }
+        self.fetch.print_results(palette);
+    }
 }
 
 impl RepositoryCommand for FastForward {
+    type Report = FfReport;
+
     fn only_changes(&self) -> bool {
         self.fetch_command.only_changes()
     }
 
-    fn project_args(&self) -> &HashSet<String> {
-        self.fetch_command.project_args()
+    fn jobs(&self) -> usize {
+        self.fetch_command.jobs()
+    }
+
+    fn project_filter(&self) -> &ProjectFilter {
+        self.fetch_command.project_filter()
     }
 
     fn run_project(
         &self,
         project: &Project,
         repository: &git2::Repository,
-    ) -> Result<RepositoryStatus, Error> {
+    ) -> Result<FfReport, Error> {
         self.fetch_command
             .run_project(project, repository)
-            .and_then(|project_status| do_ff(repository, project_status))
+            .and_then(|project_status| self.do_ff(repository, project_status))
     }
 }
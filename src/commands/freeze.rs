@@ -0,0 +1,117 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::common::describe_dirty_state;
+use super::common::exit_codes;
+use super::common::format_message_line;
+use super::common::format_project_header;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Workspace;
+use crate::data::lockfile;
+use crate::data::lockfile::LockedProject;
+use crate::data::lockfile::Lockfile;
+use crate::data::status::dirty_state;
+use crate::data::status::DirtyState;
+
+pub struct Freeze {
+    /// The file to write the lockfile to.
+    pub lockfile: PathBuf,
+}
+
+fn lock_project(repo: &git2::Repository) -> Result<LockedProject, Error> {
+    let head = repo.head()?;
+    Ok(LockedProject {
+        branch: if head.is_branch() {
+            head.shorthand().map(str::to_string)
+        } else {
+            None
+        },
+        commit: head.peel_to_commit()?.id().to_string(),
+    })
+}
+
+impl Freeze {
+    /// Record the `HEAD` of each repository in the workspace, and whether any
+    /// repository failed to open. Missing repositories are left out.
+    fn make_lockfile(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> (Lockfile, bool) {
+        let mut lockfile = Lockfile::default();
+        let mut failed = false;
+
+        for project in &workspace.projects {
+            let project_dir = working_dir.join(&project.path);
+            let locked = if project_dir.exists() {
+                git2::Repository::open(&project_dir)
+                    .map_err(Error::from)
+                    .and_then(|repo| Ok((lock_project(&repo)?, dirty_state(&repo))))
+                    .map(Some)
+            } else {
+                Ok(None)
+            };
+
+            println!("{}", format_project_header(project, palette));
+            match &locked {
+                Ok(Some((locked, dirty))) => {
+                    let at = match &locked.branch {
+                        Some(branch) => format!("{} at {:.7}", branch, locked.commit),
+                        None => format!("Detached HEAD at {:.7}", locked.commit),
+                    };
+                    println!("{}", palette.clean.paint(format_message_line(&at)));
+                    if *dirty != DirtyState::Clean {
+                        println!(
+                            "{} {}",
+                            format_message_line(&describe_dirty_state(dirty, palette).to_string()),
+                            palette.dirty.paint("(not recorded)")
+                        );
+                    }
+                }
+                Ok(None) => println!(
+                    "{}",
+                    palette
+                        .missing
+                        .paint(format_message_line("Missing repository"))
+                ),
+                Err(err) => println!(
+                    "{}",
+                    palette
+                        .error
+                        .paint(format_message_line(&format!("Error: {}", err)))
+                ),
+            }
+
+            match locked {
+                Ok(Some((locked, _))) => {
+                    lockfile.projects.insert(project.path.clone(), locked);
+                }
+                Ok(None) => {}
+                Err(_) => failed = true,
+            }
+        }
+
+        (lockfile, failed)
+    }
+}
+
+impl DirectoryCommand for Freeze {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        let (lockfile, failed) = self.make_lockfile(working_dir, workspace, palette);
+        std::fs::write(&self.lockfile, lockfile::serialize(&lockfile))?;
+        println!("Wrote {}", self.lockfile.display());
+        Ok(if failed {
+            exit_codes::INTERNAL_ERROR
+        } else {
+            exit_codes::OK
+        })
+    }
+}
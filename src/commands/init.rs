@@ -1 +1,92 @@
+use std::collections::BTreeSet;
+use std::path::Path;
 
+use super::common::exit_codes;
+use super::common::find_repositories;
+use super::common::format_message_line;
+use super::common::format_path_header;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Project;
+use crate::config::data::Remote;
+use crate::config::data::Workspace;
+use crate::config::parse::legacy;
+
+pub struct Init {}
+
+fn read_remote(repo: &git2::Repository, name: &str) -> Result<Option<Remote>, Error> {
+    let remote = repo.find_remote(name)?;
+    Ok(remote.url().map(|url| Remote {
+        name: name.to_string(),
+        url: url.to_string(),
+    }))
+}
+
+fn make_project(path: String, repo: &git2::Repository) -> Result<Option<Project>, Error> {
+    let mut remotes: Vec<Remote> = Vec::new();
+    for name in repo.remotes()?.iter().flatten() {
+        if let Some(remote) = read_remote(repo, name)? {
+            remotes.push(remote);
+        }
+    }
+    remotes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let main_remote_index = remotes
+        .iter()
+        .position(|remote| remote.name == "origin")
+        .unwrap_or(0);
+
+    if remotes.is_empty() {
+        Ok(None)
+    } else {
+        let main_remote = remotes.remove(main_remote_index);
+        Ok(Some(Project {
+            path,
+            default_branch: None,
+            groups: BTreeSet::new(),
+            submodules: true,
+            main_remote,
+            extra_remotes: remotes,
+        }))
+    }
+}
+
+impl Init {
+    pub fn run(&self, working_dir: &Path, palette: &Palette) -> Result<i32, Error> {
+        let ws_file_path = working_dir.join(".projects.gws");
+
+        // A .projects.toml would take precedence over the new file
+        for existing in &[working_dir.join(".projects.toml"), ws_file_path.clone()] {
+            if existing.exists() {
+                eprintln!("Projects file already exists: {:?}", existing);
+                return Ok(exit_codes::USER_ERROR);
+            }
+        }
+
+        let mut projects: BTreeSet<Project> = BTreeSet::new();
+
+        for path in find_repositories(working_dir)? {
+            println!("{}", format_path_header(&path, palette));
+            let repo = git2::Repository::open(working_dir.join(&path))?;
+
+            match make_project(path, &repo)? {
+                Some(project) => {
+                    projects.insert(project);
+                    println!("{}", palette.clean.paint(format_message_line("Added")));
+                }
+                None => {
+                    println!(
+                        "{}",
+                        palette
+                            .missing
+                            .paint(format_message_line("No remotes, skipped"))
+                    );
+                }
+            }
+        }
+
+        std::fs::write(&ws_file_path, legacy::serialize(&Workspace::from(projects)))?;
+
+        Ok(exit_codes::OK)
+    }
+}
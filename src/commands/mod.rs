@@ -1,9 +1,19 @@
+pub mod branch;
 pub mod check;
 pub mod clone;
 pub mod common;
+pub mod convert;
 pub mod error;
+pub mod exec;
 pub mod fetch;
 pub mod ff;
+pub mod freeze;
 pub mod init;
+pub mod prune;
+pub mod prune_branches;
+pub mod push;
+pub mod remotes;
+pub mod restore;
 pub mod status;
+pub mod switch;
 pub mod update;
@@ -0,0 +1,328 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::io::BufRead;
+use std::io::Write;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::check::Check;
+use super::check::RepositoryPresence;
+use super::common::describe_dirty_state;
+use super::common::describe_operation;
+use super::common::describe_stashes;
+use super::common::exit_codes;
+use super::common::find_repositories;
+use super::common::format_message_line;
+use super::common::format_path_header;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Workspace;
+use crate::data::status::dirty_state;
+use crate::data::status::ongoing_operation;
+use crate::data::status::stash_count;
+use crate::data::status::DirtyState;
+use crate::data::status::OngoingOperation;
+
+pub struct Prune {
+    /// Move pruned repositories here instead of deleting them.
+    pub archive_dir: Option<PathBuf>,
+    /// Don't ask for confirmation.
+    pub assume_yes: bool,
+}
+
+/// A reason why a repository cannot be pruned without losing work.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PruneBlocker {
+    Dirty(DirtyState),
+    Operation(OngoingOperation),
+    Stashes(usize),
+    /// The branch is ahead of its upstream by this many commits.
+    UnpushedCommits(String, usize),
+    /// The branch has no upstream, and its head is not contained in any
+    /// remote-tracking branch.
+    UnpushedBranch(String),
+    /// `HEAD` is detached at a commit not contained in any remote-tracking
+    /// branch.
+    UnpushedDetachedHead,
+    /// This workspace project or other repository is inside the repository,
+    /// and would be pruned with it.
+    ContainsRepository(String),
+}
+
+impl PruneBlocker {
+    fn describe(&self, palette: &Palette) -> String {
+        match self {
+            PruneBlocker::Dirty(dirty) => describe_dirty_state(dirty, palette).to_string(),
+            PruneBlocker::Operation(operation) => {
+                describe_operation(*operation, palette).to_string()
+            }
+            PruneBlocker::Stashes(stashes) => describe_stashes(*stashes, palette).to_string(),
+            PruneBlocker::UnpushedCommits(branch, ahead) => palette
+                .dirty
+                .paint(format!("Branch {} has {} unpushed commits", branch, ahead))
+                .to_string(),
+            PruneBlocker::UnpushedBranch(branch) => palette
+                .dirty
+                .paint(format!("Branch {} is not pushed to any remote", branch))
+                .to_string(),
+            PruneBlocker::UnpushedDetachedHead => palette
+                .dirty
+                .paint("Detached HEAD is not pushed to any remote")
+                .to_string(),
+            PruneBlocker::ContainsRepository(path) => palette
+                .dirty
+                .paint(format!("Contains {}", path))
+                .to_string(),
+        }
+    }
+}
+
+/// Whether `oid` is reachable from any remote-tracking branch.
+fn is_on_remote(repo: &git2::Repository, oid: git2::Oid) -> Result<bool, Error> {
+    for branch in repo.branches(Some(git2::BranchType::Remote))? {
+        let (branch, _) = branch?;
+        if let Some(remote_oid) = branch.get().target() {
+            if remote_oid == oid || repo.graph_descendant_of(remote_oid, oid)? {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn find_blockers(repo: &git2::Repository) -> Result<Vec<PruneBlocker>, Error> {
+    let mut blockers = Vec::new();
+
+    let dirty = dirty_state(repo);
+    if dirty != DirtyState::Clean {
+        blockers.push(PruneBlocker::Dirty(dirty));
+    }
+    if let Some(operation) = ongoing_operation(repo) {
+        blockers.push(PruneBlocker::Operation(operation));
+    }
+    let stashes = stash_count(repo);
+    if stashes > 0 {
+        blockers.push(PruneBlocker::Stashes(stashes));
+    }
+    if repo.head_detached()? && !is_on_remote(repo, repo.head()?.peel_to_commit()?.id())? {
+        blockers.push(PruneBlocker::UnpushedDetachedHead);
+    }
+
+    for branch in repo.branches(Some(git2::BranchType::Local))? {
+        let (branch, _) = branch?;
+        let name = branch.name()?.ok_or(Error::NoBranchNameFound)?.to_string();
+        let oid = branch.get().peel_to_commit()?.id();
+
+        // A gone upstream counts as no upstream
+        match branch.upstream() {
+            Ok(upstream) => {
+                let upstream_oid = upstream.get().peel_to_commit()?.id();
+                let (ahead, _) = repo.graph_ahead_behind(oid, upstream_oid)?;
+                if ahead > 0 {
+                    blockers.push(PruneBlocker::UnpushedCommits(name, ahead));
+                }
+            }
+            Err(_) => {
+                if !is_on_remote(repo, oid)? {
+                    blockers.push(PruneBlocker::UnpushedBranch(name));
+                }
+            }
+        }
+    }
+
+    Ok(blockers)
+}
+
+/// The ignored files and directories in the working tree of `repo`, which
+/// are deleted along with it.
+fn ignored_files(repo: &git2::Repository) -> Result<Vec<String>, Error> {
+    let mut options = git2::StatusOptions::new();
+    options.include_ignored(true).recurse_ignored_dirs(false);
+    Ok(repo
+        .statuses(Some(&mut options))?
+        .iter()
+        .filter(|entry| entry.status().is_ignored())
+        .flat_map(|entry| entry.path().map(str::to_string))
+        .collect())
+}
+
+/// `path` with `.` and `..` components removed.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+fn confirm(question: &str) -> Result<bool, Error> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+impl Prune {
+    /// Find the repositories that are neither listed nor ignored, and what
+    /// prevents each of them from being pruned.
+    pub fn make_report(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+    ) -> Result<BTreeMap<String, Vec<PruneBlocker>>, Error> {
+        let presences = (Check {}).make_report(working_dir, workspace)?;
+        let mut report = BTreeMap::new();
+        for (path, presence) in &presences {
+            if *presence == RepositoryPresence::Unknown {
+                let repo = git2::Repository::open(working_dir.join(path))?;
+                let mut blockers = find_blockers(&repo)?;
+
+                // Pruning the directory would take these along with it
+                let prefix = format!("{}/", path);
+                let nested: BTreeSet<String> = presences
+                    .keys()
+                    .filter(|other| other.starts_with(&prefix))
+                    .filter(|other| working_dir.join(other).exists())
+                    .cloned()
+                    .chain(
+                        find_repositories(&working_dir.join(path))?
+                            .into_iter()
+                            .map(|inner| format!("{}{}", prefix, inner)),
+                    )
+                    .collect();
+                blockers.extend(nested.into_iter().map(PruneBlocker::ContainsRepository));
+
+                report.insert(path.clone(), blockers);
+            }
+        }
+        Ok(report)
+    }
+
+    fn prune(&self, working_dir: &Path, path: &str) -> Result<(), Error> {
+        match &self.archive_dir {
+            Some(archive_dir) => {
+                let target = archive_dir.join(path);
+                if target.exists() {
+                    return Err(Error::from(std::io::Error::new(
+                        std::io::ErrorKind::AlreadyExists,
+                        format!("{:?} already exists", target),
+                    )));
+                }
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::rename(working_dir.join(path), target)?;
+            }
+            None => std::fs::remove_dir_all(working_dir.join(path))?,
+        }
+        Ok(())
+    }
+}
+
+impl DirectoryCommand for Prune {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        if let Some(archive_dir) = &self.archive_dir {
+            // Archived repositories would show up as unknown on the next run
+            if normalize(archive_dir).starts_with(normalize(working_dir)) {
+                eprintln!(
+                    "Archive directory is inside the workspace: {:?}",
+                    archive_dir
+                );
+                return Ok(exit_codes::USER_ERROR);
+            }
+        }
+
+        let report = self.make_report(working_dir, workspace)?;
+        let action = if self.archive_dir.is_some() {
+            "archive"
+        } else {
+            "delete"
+        };
+
+        for (path, blockers) in &report {
+            println!("{}", format_path_header(path, palette));
+            if blockers.is_empty() {
+                println!(
+                    "{}",
+                    palette
+                        .missing
+                        .paint(format_message_line(&format!("Will {}", action)))
+                );
+                if self.archive_dir.is_none() {
+                    let repo = git2::Repository::open(working_dir.join(path))?;
+                    let ignored = ignored_files(&repo)?;
+                    if !ignored.is_empty() {
+                        println!(
+                            "{}",
+                            palette.dirty.paint(format_message_line(&format!(
+                                "Including ignored {}",
+                                ignored.join(", ")
+                            )))
+                        );
+                    }
+                }
+            } else {
+                for blocker in blockers {
+                    println!("{}", format_message_line(&blocker.describe(palette)));
+                }
+            }
+        }
+
+        let prunable: Vec<&String> = report
+            .iter()
+            .filter(|(_, blockers)| blockers.is_empty())
+            .map(|(path, _)| path)
+            .collect();
+        let all_prunable = prunable.len() == report.len();
+
+        if prunable.is_empty() {
+            println!("Nothing to prune.");
+        } else if self.assume_yes
+            || confirm(&format!(
+                "{}{} {} repositories{}?",
+                &action[..1].to_uppercase(),
+                &action[1..],
+                prunable.len(),
+                if self.archive_dir.is_some() {
+                    ""
+                } else {
+                    ", including their ignored files"
+                }
+            ))?
+        {
+            let mut failed = false;
+            for path in prunable {
+                if let Err(err) = self.prune(working_dir, path) {
+                    eprintln!("Failed to {} {}: {}", action, path, err);
+                    failed = true;
+                }
+            }
+            if failed {
+                return Ok(exit_codes::INTERNAL_ERROR);
+            }
+        } else {
+            println!("Aborted.");
+            return Ok(exit_codes::OK);
+        }
+
+        Ok(if all_prunable {
+            exit_codes::OK
+        } else {
+            exit_codes::INCONSISTENT_WORKSPACE
+        })
+    }
+}
@@ -0,0 +1,187 @@
+use std::path::Path;
+
+use super::common::check_path_args;
+use super::common::default_remote_branch;
+use super::common::exit_codes;
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
+use super::common::format_project_header;
+use super::common::former_upstream;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Project;
+use crate::config::data::Workspace;
+use crate::data::status::repository_status;
+
+pub struct PruneBranches {
+    pub projects: ProjectFilter,
+    /// Only report which branches would be deleted.
+    pub dry_run: bool,
+}
+
+/// A local branch whose upstream no longer exists.
+#[derive(Debug, Eq, PartialEq)]
+pub struct GoneBranch {
+    pub branch: String,
+    pub upstream_name: String,
+    pub outcome: GoneBranchOutcome,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum GoneBranchOutcome {
+    Deleted,
+    /// Not deleted because of a dry run.
+    WouldDelete,
+    /// Kept because it has commits in neither the default branch nor the
+    /// former upstream.
+    NotMerged,
+    /// Kept because neither the default branch of the main remote nor the
+    /// former upstream is known.
+    NoDefaultBranch,
+    /// Kept because it is checked out.
+    CheckedOut,
+}
+
+impl GoneBranch {
+    fn describe(&self, palette: &Palette) -> String {
+        let message = |text: &str| {
+            format_message_line(&format!(
+                "{} (upstream {} is gone): {}",
+                self.branch, self.upstream_name, text
+            ))
+        };
+        match self.outcome {
+            GoneBranchOutcome::Deleted => palette.cloning.paint(message("Deleted")),
+            GoneBranchOutcome::WouldDelete => palette.dirty.paint(message("Would delete")),
+            GoneBranchOutcome::NotMerged => palette.missing.paint(message(
+                "Not merged into default branch or former upstream, kept",
+            )),
+            GoneBranchOutcome::NoDefaultBranch => palette.missing.paint(message(
+                "Default branch and former upstream not known, kept",
+            )),
+            GoneBranchOutcome::CheckedOut => palette.missing.paint(message("Checked out, kept")),
+        }
+        .to_string()
+    }
+}
+
+impl PruneBranches {
+    fn prune_project(
+        &self,
+        repo: &git2::Repository,
+        project: &Project,
+    ) -> Result<Vec<GoneBranch>, Error> {
+        let default_head: Option<git2::Oid> = default_remote_branch(repo, project)
+            .ok()
+            .and_then(|branch| branch.get().target());
+
+        let mut result = Vec::new();
+        for status in repository_status(repo)?
+            .iter()
+            .filter(|b| b.upstream_gone())
+        {
+            let mut branch = repo.find_branch(&status.name, git2::BranchType::Local)?;
+            let head = branch.get().peel_to_commit()?.id();
+
+            let targets: Vec<git2::Oid> = default_head
+                .into_iter()
+                .chain(former_upstream(repo, &status.name))
+                .collect();
+            let mut merged = false;
+            for target in &targets {
+                merged = merged || head == *target || repo.graph_descendant_of(*target, head)?;
+            }
+
+            let outcome = if status.is_head {
+                GoneBranchOutcome::CheckedOut
+            } else if merged && self.dry_run {
+                GoneBranchOutcome::WouldDelete
+            } else if merged {
+                branch.delete()?;
+                GoneBranchOutcome::Deleted
+            } else if targets.is_empty() {
+                GoneBranchOutcome::NoDefaultBranch
+            } else {
+                GoneBranchOutcome::NotMerged
+            };
+
+            result.push(GoneBranch {
+                branch: status.name.clone(),
+                upstream_name: status.upstream_name.clone().unwrap_or_default(),
+                outcome,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Delete, or with `dry_run` only find, the merged branches with a gone
+    /// upstream in each selected project. Missing repositories are skipped.
+    pub fn make_report<'ws>(
+        &self,
+        working_dir: &Path,
+        workspace: &'ws Workspace,
+    ) -> Vec<(&'ws Project, Result<Vec<GoneBranch>, Error>)> {
+        workspace
+            .projects
+            .iter()
+            .filter(|project| self.projects.matches(project))
+            .filter(|project| working_dir.join(&project.path).exists())
+            .map(|project| {
+                (
+                    project,
+                    git2::Repository::open(working_dir.join(&project.path))
+                        .map_err(Error::from)
+                        .and_then(|repo| self.prune_project(&repo, project)),
+                )
+            })
+            .collect()
+    }
+}
+
+impl DirectoryCommand for PruneBranches {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        if let Some(exit_code) = check_path_args(&self.projects, workspace) {
+            return Ok(exit_code);
+        }
+
+        let report = self.make_report(working_dir, workspace);
+        let mut exit_code = exit_codes::OK;
+        let mut found = false;
+
+        for (project, result) in &report {
+            match result {
+                Ok(branches) if branches.is_empty() => {}
+                Ok(branches) => {
+                    found = true;
+                    println!("{}", format_project_header(project, palette));
+                    for branch in branches {
+                        println!("{}", branch.describe(palette));
+                    }
+                }
+                Err(err) => {
+                    exit_code = exit_codes::INTERNAL_ERROR;
+                    println!("{}", format_project_header(project, palette));
+                    println!(
+                        "{}",
+                        palette
+                            .error
+                            .paint(format_message_line(&format!("Error: {}", err)))
+                    );
+                }
+            }
+        }
+
+        if !found && exit_code == exit_codes::OK {
+            println!("No branches with a gone upstream");
+        }
+
+        Ok(exit_code)
+    }
+}
@@ -0,0 +1,188 @@
+use serde::Serialize;
+
+use super::common::credentials::Credentials;
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
+use super::common::RepositoryCommand;
+use super::common::RepositoryReport;
+use super::error::Error;
+use super::status::Status;
+use crate::color::palette::Palette;
+use crate::config::data::Project;
+use crate::data::status::BranchStatus;
+use crate::data::status::PushOutcome;
+use crate::data::status::PushResult;
+use crate::data::status::RepositoryStatus;
+
+pub struct Push {
+    pub status_command: Status,
+    pub jobs: usize,
+    pub credentials: Credentials,
+    /// Only report which branches would be pushed.
+    pub dry_run: bool,
+    /// Only push the checked out branch.
+    pub only_head: bool,
+}
+
+/// Push `branch` to the branch it tracks on its upstream remote. Returns the
+/// reason if the remote rejected the update.
+fn push_branch(
+    repo: &git2::Repository,
+    branch: &str,
+    credentials: &Credentials,
+) -> Result<Option<String>, Error> {
+    let local_ref = format!("refs/heads/{}", branch);
+    let remote_name = repo.branch_upstream_remote(&local_ref)?;
+    let remote_name = remote_name.as_str().ok_or(Error::NoBranchNameFound)?;
+    let merge_ref = repo
+        .config()?
+        .get_string(&format!("branch.{}.merge", branch))?;
+
+    let mut remote = repo.find_remote(remote_name)?;
+    let mut rejection: Option<String> = None;
+    {
+        let mut callbacks = credentials.remote_callbacks();
+        callbacks.push_update_reference(|_, status| {
+            if let Some(status) = status {
+                rejection = Some(status.to_string());
+            }
+            Ok(())
+        });
+        let mut push_options = git2::PushOptions::new();
+        push_options.remote_callbacks(callbacks);
+        remote.push(
+            &[format!("{}:{}", local_ref, merge_ref)],
+            Some(&mut push_options),
+        )?;
+    }
+    Ok(rejection)
+}
+
+impl Push {
+    /// Whether `branch` should be pushed: it must have an upstream that it is
+    /// strictly ahead of.
+    fn should_push(&self, branch: &BranchStatus) -> bool {
+        branch.upstream_name.is_some()
+            && !branch.upstream_gone()
+            && branch.ahead > 0
+            && branch.behind == 0
+            && (branch.is_head || !self.only_head)
+    }
+
+    fn push(&self, repo: &git2::Repository, branch: &BranchStatus) -> PushResult {
+        let outcome = if self.dry_run {
+            PushOutcome::WouldPush
+        } else {
+            match push_branch(repo, &branch.name, &self.credentials) {
+                Ok(None) => PushOutcome::Pushed,
+                Ok(Some(rejection)) => PushOutcome::Failed(rejection),
+                Err(err) => PushOutcome::Failed(err.to_string()),
+            }
+        };
+
+        PushResult {
+            branch: branch.name.clone(),
+            upstream_name: branch.upstream_name.clone().unwrap_or_default(),
+            commits: branch.ahead,
+            outcome,
+        }
+    }
+}
+
+/// The status of a project after pushing it, and the branches pushed to their
+/// upstreams.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct PushReport {
+    #[serde(flatten)]
+    pub status: RepositoryStatus,
+    pub push_results: Vec<PushResult>,
+}
+
+impl RepositoryReport for PushReport {
+    fn status(&self) -> &RepositoryStatus {
+        &self.status
+    }
+
+    fn has_changes(&self) -> bool {
+        !self.push_results.is_empty()
+    }
+
+    fn failed(&self) -> bool {
+        self.push_results
+            .iter()
+            .any(|r| matches!(r.outcome, PushOutcome::Failed(_)))
+    }
+
+    fn print_results(&self, palette: &Palette) {
+        for push_result in &self.push_results {
+            println!("{}", describe_push_result(push_result, palette));
+        }
+    }
+}
+
+fn describe_push_result(result: &PushResult, palette: &Palette) -> String {
+    let commits = if result.commits == 1 {
+        "1 commit".to_string()
+    } else {
+        format!("{} commits", result.commits)
+    };
+    match &result.outcome {
+        PushOutcome::Pushed => palette.cloning.paint(format_message_line(&format!(
+            "Pushed {} to {} ({})",
+            result.branch, result.upstream_name, commits
+        ))),
+        PushOutcome::WouldPush => palette.dirty.paint(format_message_line(&format!(
+            "Would push {} to {} ({})",
+            result.branch, result.upstream_name, commits
+        ))),
+        PushOutcome::Failed(message) => palette.error.paint(format_message_line(&format!(
+            "Failed to push {} to {}: {}",
+            result.branch, result.upstream_name, message
+        ))),
+    }
+    .to_string()
+}
+
+impl RepositoryCommand for Push {
+    type Report = PushReport;
+
+    fn only_changes(&self) -> bool {
+        self.status_command.only_changes()
+    }
+
+    fn jobs(&self) -> usize {
+        self.jobs
+    }
+
+    fn project_filter(&self) -> &ProjectFilter {
+        self.status_command.project_filter()
+    }
+
+    fn run_project(
+        &self,
+        project: &Project,
+        repository: &git2::Repository,
+    ) -> Result<PushReport, Error> {
+        let status_before = self.status_command.run_project(project, repository)?;
+
+        let push_results: Vec<PushResult> = status_before
+            .iter()
+            .filter(|branch| self.should_push(branch))
+            .map(|branch| self.push(repository, branch))
+            .collect();
+
+        let status_after = if push_results
+            .iter()
+            .any(|result| result.outcome == PushOutcome::Pushed)
+        {
+            self.status_command.run_project(project, repository)?
+        } else {
+            status_before
+        };
+
+        Ok(PushReport {
+            status: status_after,
+            push_results,
+        })
+    }
+}
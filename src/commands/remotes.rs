@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::common::check_path_args;
+use super::common::exit_codes;
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
+use super::common::format_project_header;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Project;
+use crate::config::data::Remote;
+use crate::config::data::Workspace;
+
+pub struct SyncRemotes {
+    pub projects: ProjectFilter,
+    /// Only report what would change.
+    pub dry_run: bool,
+}
+
+/// A difference between the remotes in the projects list and those in a clone.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RemoteChange {
+    /// The remote is in the projects list but not in the clone.
+    Add(Remote),
+    /// The remote's URL in the clone differs from the projects list.
+    SetUrl { remote: Remote, old_url: String },
+    /// The remote is in the clone but not in the projects list. These are
+    /// reported but never removed.
+    Unlisted(String),
+}
+
+impl RemoteChange {
+    fn apply(&self, repo: &git2::Repository) -> Result<(), git2::Error> {
+        match self {
+            RemoteChange::Add(remote) => repo.remote(&remote.name, &remote.url).map(|_| ()),
+            RemoteChange::SetUrl { remote, .. } => repo.remote_set_url(&remote.name, &remote.url),
+            RemoteChange::Unlisted(_) => Ok(()),
+        }
+    }
+
+    fn describe(&self, dry_run: bool) -> String {
+        match (self, dry_run) {
+            (RemoteChange::Add(remote), false) => {
+                format!("Added remote {}: {}", remote.name, remote.url)
+            }
+            (RemoteChange::Add(remote), true) => {
+                format!("Would add remote {}: {}", remote.name, remote.url)
+            }
+            (RemoteChange::SetUrl { remote, old_url }, false) => format!(
+                "Changed URL of remote {}: {} -> {}",
+                remote.name, old_url, remote.url
+            ),
+            (RemoteChange::SetUrl { remote, old_url }, true) => format!(
+                "Would change URL of remote {}: {} -> {}",
+                remote.name, old_url, remote.url
+            ),
+            (RemoteChange::Unlisted(name), _) => {
+                format!("Remote {} is not in the projects list", name)
+            }
+        }
+    }
+}
+
+/// The changes needed to make the remotes in `repo` match `project`.
+pub fn plan_remote_changes(
+    repo: &git2::Repository,
+    project: &Project,
+) -> Result<Vec<RemoteChange>, Error> {
+    let mut changes = Vec::new();
+
+    for remote in project.remotes() {
+        match repo.find_remote(&remote.name) {
+            Ok(existing) => {
+                let old_url = existing.url().unwrap_or("");
+                if old_url != remote.url {
+                    changes.push(RemoteChange::SetUrl {
+                        remote: remote.clone(),
+                        old_url: old_url.to_string(),
+                    });
+                }
+            }
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                changes.push(RemoteChange::Add(remote.clone()));
+            }
+            Err(err) => return Err(Error::from(err)),
+        }
+    }
+
+    for name in repo.remotes()?.iter().flatten() {
+        if !project.remotes().iter().any(|remote| remote.name == name) {
+            changes.push(RemoteChange::Unlisted(name.to_string()));
+        }
+    }
+
+    Ok(changes)
+}
+
+impl SyncRemotes {
+    /// The remote changes for each selected project that exists in the
+    /// workspace.
+    pub fn make_report(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+    ) -> Result<BTreeMap<String, Vec<RemoteChange>>, Error> {
+        let mut report = BTreeMap::new();
+        for project in workspace
+            .projects
+            .iter()
+            .filter(|p| self.projects.matches(p))
+        {
+            if let Ok(repo) = git2::Repository::open(working_dir.join(&project.path)) {
+                report.insert(project.path.clone(), plan_remote_changes(&repo, project)?);
+            }
+        }
+        Ok(report)
+    }
+}
+
+impl DirectoryCommand for SyncRemotes {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        if let Some(exit_code) = check_path_args(&self.projects, workspace) {
+            return Ok(exit_code);
+        }
+
+        let report = self.make_report(working_dir, workspace)?;
+        let mut failed = false;
+
+        for project in workspace
+            .projects
+            .iter()
+            .filter(|p| self.projects.matches(p))
+        {
+            println!("{}", format_project_header(project, palette));
+
+            let changes = match report.get(&project.path) {
+                Some(changes) => changes,
+                None => {
+                    println!(
+                        "{}",
+                        palette
+                            .missing
+                            .paint(format_message_line("Missing repository"))
+                    );
+                    continue;
+                }
+            };
+
+            if changes.is_empty() {
+                println!("{}", palette.clean.paint(format_message_line("Up to date")));
+            }
+
+            let repo = git2::Repository::open(working_dir.join(&project.path))?;
+            for change in changes {
+                let message = format_message_line(&change.describe(self.dry_run));
+                if let RemoteChange::Unlisted(_) = change {
+                    println!("{}", palette.missing.paint(message));
+                } else if self.dry_run {
+                    println!("{}", palette.dirty.paint(message));
+                } else {
+                    match change.apply(&repo) {
+                        Ok(()) => println!("{}", palette.clean.paint(message)),
+                        Err(err) => {
+                            eprintln!(
+                                "Failed to update remotes of project {}: {}",
+                                project.path, err
+                            );
+                            println!("{}", palette.error.paint(message));
+                            failed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(if failed {
+            exit_codes::INTERNAL_ERROR
+        } else {
+            exit_codes::OK
+        })
+    }
+}
@@ -0,0 +1,258 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use super::common::describe_dirty_state;
+use super::common::describe_operation;
+use super::common::exit_codes;
+use super::common::format_message_line;
+use super::common::format_path_header;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Workspace;
+use crate::data::lockfile;
+use crate::data::lockfile::LockedProject;
+use crate::data::lockfile::Lockfile;
+use crate::data::status::dirty_state;
+use crate::data::status::ongoing_operation;
+use crate::data::status::DirtyState;
+use crate::data::status::OngoingOperation;
+
+pub struct Restore {
+    /// The lockfile written by `gws freeze`.
+    pub lockfile: PathBuf,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum RestoreOutcome {
+    /// `HEAD` was already as recorded.
+    AlreadyRestored,
+    /// The recorded branch was checked out. It still points to the recorded
+    /// commit.
+    CheckedOutBranch,
+    /// The recorded commit was checked out as a detached `HEAD`, because no
+    /// branch was recorded or the branch no longer points to that commit.
+    Detached,
+    Missing,
+    /// The project is in the workspace but not in the lockfile.
+    NotLocked,
+    /// The project is in the lockfile but not in the workspace.
+    NotInWorkspace,
+    Dirty(DirtyState),
+    Operation(OngoingOperation),
+    /// The recorded commit does not exist in the repository.
+    CommitNotFound,
+}
+
+impl RestoreOutcome {
+    fn exit_code(&self) -> i32 {
+        match self {
+            RestoreOutcome::AlreadyRestored
+            | RestoreOutcome::CheckedOutBranch
+            | RestoreOutcome::Detached
+            | RestoreOutcome::Missing
+            | RestoreOutcome::NotLocked
+            | RestoreOutcome::NotInWorkspace => exit_codes::OK,
+            RestoreOutcome::Dirty(_)
+            | RestoreOutcome::Operation(_)
+            | RestoreOutcome::CommitNotFound => exit_codes::INCONSISTENT_WORKSPACE,
+        }
+    }
+}
+
+fn restore_project(
+    repo: &git2::Repository,
+    locked: &LockedProject,
+) -> Result<RestoreOutcome, Error> {
+    let dirty = dirty_state(repo);
+    if dirty != DirtyState::Clean {
+        return Ok(RestoreOutcome::Dirty(dirty));
+    }
+    if let Some(operation) = ongoing_operation(repo) {
+        return Ok(RestoreOutcome::Operation(operation));
+    }
+
+    let oid = git2::Oid::from_str(&locked.commit)?;
+    let commit = match repo.find_commit(oid) {
+        Ok(commit) => commit,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {
+            return Ok(RestoreOutcome::CommitNotFound)
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    // Only check out the branch if that doesn't also require moving it
+    let branch_ref: Option<String> = locked
+        .branch
+        .as_ref()
+        .and_then(|name| repo.find_branch(name, git2::BranchType::Local).ok())
+        .filter(|branch| branch.get().target() == Some(oid))
+        .and_then(|branch| branch.get().name().map(str::to_string));
+
+    if let Ok(head) = repo.head() {
+        let head_ref = if head.is_branch() {
+            head.name().map(str::to_string)
+        } else {
+            None
+        };
+        if head.target() == Some(oid) && head_ref == branch_ref {
+            return Ok(RestoreOutcome::AlreadyRestored);
+        }
+    }
+
+    repo.checkout_tree(
+        commit.as_object(),
+        Some(git2::build::CheckoutBuilder::new().safe()),
+    )?;
+    match branch_ref {
+        Some(branch_ref) => {
+            repo.set_head(&branch_ref)?;
+            Ok(RestoreOutcome::CheckedOutBranch)
+        }
+        None => {
+            repo.set_head_detached(oid)?;
+            Ok(RestoreOutcome::Detached)
+        }
+    }
+}
+
+fn print_outcome(
+    path: &str,
+    locked: Option<&LockedProject>,
+    outcome: &Result<RestoreOutcome, Error>,
+    palette: &Palette,
+) {
+    println!("{}", format_path_header(path, palette));
+
+    let short_commit = locked
+        .map(|locked| format!("{:.7}", locked.commit))
+        .unwrap_or_default();
+    let branch = locked.and_then(|locked| locked.branch.as_deref());
+
+    let line = match outcome {
+        Ok(RestoreOutcome::AlreadyRestored) => {
+            palette.clean.paint(format_message_line(&match branch {
+                Some(branch) => format!("Already at {} ({})", branch, short_commit),
+                None => format!("Already at {}", short_commit),
+            }))
+        }
+        Ok(RestoreOutcome::CheckedOutBranch) => {
+            palette.cloning.paint(format_message_line(&format!(
+                "Checked out {} at {}",
+                branch.unwrap_or_default(),
+                short_commit
+            )))
+        }
+        Ok(RestoreOutcome::Detached) => palette.cloning.paint(format_message_line(&match branch {
+            Some(branch) => format!("Detached HEAD at {} ({} has moved)", short_commit, branch),
+            None => format!("Detached HEAD at {}", short_commit),
+        })),
+        Ok(RestoreOutcome::Missing) => palette
+            .missing
+            .paint(format_message_line("Missing repository")),
+        Ok(RestoreOutcome::NotLocked) => palette
+            .missing
+            .paint(format_message_line("Not in lockfile, skipped")),
+        Ok(RestoreOutcome::NotInWorkspace) => palette
+            .missing
+            .paint(format_message_line("Not in workspace, skipped")),
+        Ok(RestoreOutcome::Dirty(dirty)) => {
+            println!(
+                "{}",
+                format_message_line(&describe_dirty_state(dirty, palette).to_string())
+            );
+            palette.error.paint(format_message_line("Not restored"))
+        }
+        Ok(RestoreOutcome::Operation(operation)) => {
+            println!(
+                "{}",
+                format_message_line(&describe_operation(*operation, palette).to_string())
+            );
+            palette.error.paint(format_message_line("Not restored"))
+        }
+        Ok(RestoreOutcome::CommitNotFound) => palette.error.paint(format_message_line(&format!(
+            "Commit {} not found, not restored",
+            short_commit
+        ))),
+        Err(err) => palette
+            .error
+            .paint(format_message_line(&format!("Error: {}", err))),
+    };
+    println!("{}", line);
+}
+
+impl Restore {
+    /// Restore each project in `workspace` to the state recorded in
+    /// `lockfile`, and report the result for each project in the workspace or
+    /// the lockfile.
+    pub fn make_report(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        lockfile: &Lockfile,
+    ) -> Vec<(String, Result<RestoreOutcome, Error>)> {
+        let mut report: Vec<(String, Result<RestoreOutcome, Error>)> = workspace
+            .projects
+            .iter()
+            .map(|project| {
+                let project_dir = working_dir.join(&project.path);
+                let outcome = match lockfile.projects.get(&project.path) {
+                    _ if !project_dir.exists() => Ok(RestoreOutcome::Missing),
+                    None => Ok(RestoreOutcome::NotLocked),
+                    Some(locked) => git2::Repository::open(&project_dir)
+                        .map_err(Error::from)
+                        .and_then(|repo| restore_project(&repo, locked)),
+                };
+                (project.path.clone(), outcome)
+            })
+            .collect();
+
+        report.extend(
+            lockfile
+                .projects
+                .keys()
+                .filter(|path| !workspace.projects.iter().any(|p| &&p.path == path))
+                .map(|path| (path.clone(), Ok(RestoreOutcome::NotInWorkspace))),
+        );
+
+        report
+    }
+}
+
+impl DirectoryCommand for Restore {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        let lockfile = match std::fs::read_to_string(&self.lockfile)
+            .map_err(|e| e.to_string())
+            .and_then(|content| lockfile::parse(&content).map_err(|e| e.to_string()))
+        {
+            Ok(lockfile) => lockfile,
+            Err(err) => {
+                eprintln!(
+                    "Failed to read lockfile {}: {}",
+                    self.lockfile.display(),
+                    err
+                );
+                return Ok(exit_codes::USER_ERROR);
+            }
+        };
+
+        let report = self.make_report(working_dir, workspace, &lockfile);
+        for (path, outcome) in &report {
+            print_outcome(path, lockfile.projects.get(path), outcome, palette);
+        }
+
+        Ok(report
+            .iter()
+            .map(|(_, outcome)| match outcome {
+                Ok(outcome) => outcome.exit_code(),
+                Err(_) => exit_codes::INTERNAL_ERROR,
+            })
+            .find(|exit_code| *exit_code != exit_codes::OK)
+            .unwrap_or(exit_codes::OK))
+    }
+}
@@ -1,6 +1,6 @@
-use std::collections::HashSet;
-
+use super::common::filter::ProjectFilter;
 use super::common::RepositoryCommand;
+use super::common::RepositoryReport;
 use super::error::Error;
 use crate::config::data::Project;
 use crate::data::status::project_status;
@@ -8,15 +8,23 @@ use crate::data::status::RepositoryStatus;
 
 pub struct Status {
     pub only_changes: bool,
-    pub projects: HashSet<String>,
+    pub projects: ProjectFilter,
+}
+
+impl RepositoryReport for RepositoryStatus {
+    fn status(&self) -> &RepositoryStatus {
+        self
+    }
 }
 
 impl RepositoryCommand for Status {
+    type Report = RepositoryStatus;
+
     fn only_changes(&self) -> bool {
         self.only_changes
     }
 
-    fn project_args(&self) -> &HashSet<String> {
+    fn project_filter(&self) -> &ProjectFilter {
         &self.projects
     }
 
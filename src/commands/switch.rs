@@ -0,0 +1,232 @@
+use std::path::Path;
+
+use super::common::check_path_args;
+use super::common::describe_dirty_state;
+use super::common::describe_operation;
+use super::common::exit_codes;
+use super::common::filter::ProjectFilter;
+use super::common::format_message_line;
+use super::common::format_project_header;
+use super::common::DirectoryCommand;
+use super::error::Error;
+use crate::color::palette::Palette;
+use crate::config::data::Project;
+use crate::config::data::Workspace;
+use crate::data::status::dirty_state;
+use crate::data::status::ongoing_operation;
+use crate::data::status::DirtyState;
+use crate::data::status::OngoingOperation;
+
+pub struct Switch {
+    pub projects: ProjectFilter,
+    /// The name of the local branch to check out.
+    pub branch: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum SwitchOutcome {
+    AlreadyOnBranch,
+    /// The existing local branch was checked out.
+    CheckedOut,
+    /// The branch was created from, and set to track, this remote branch.
+    CreatedTracking(String),
+    /// Neither the local branch nor a matching branch on the main remote
+    /// exists.
+    NoSuchBranch,
+    Dirty(DirtyState),
+    Operation(OngoingOperation),
+    Missing,
+}
+
+impl SwitchOutcome {
+    fn moved(&self) -> bool {
+        matches!(
+            self,
+            SwitchOutcome::CheckedOut | SwitchOutcome::CreatedTracking(_)
+        )
+    }
+
+    fn exit_code(&self) -> i32 {
+        match self {
+            SwitchOutcome::Dirty(_) | SwitchOutcome::Operation(_) => {
+                exit_codes::INCONSISTENT_WORKSPACE
+            }
+            _ => exit_codes::OK,
+        }
+    }
+}
+
+fn find_branch<'repo>(
+    repo: &'repo git2::Repository,
+    name: &str,
+    branch_type: git2::BranchType,
+) -> Result<Option<git2::Branch<'repo>>, Error> {
+    match repo.find_branch(name, branch_type) {
+        Ok(branch) => Ok(Some(branch)),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn switch_project(
+    repo: &git2::Repository,
+    project: &Project,
+    branch_name: &str,
+) -> Result<SwitchOutcome, Error> {
+    if let Ok(head) = repo.head() {
+        if head.is_branch() && head.shorthand() == Some(branch_name) {
+            return Ok(SwitchOutcome::AlreadyOnBranch);
+        }
+    }
+
+    let remote_branch_name = format!("{}/{}", project.main_remote.name, branch_name);
+    let (target, create_from) = match find_branch(repo, branch_name, git2::BranchType::Local)? {
+        Some(branch) => (branch.get().peel_to_commit()?, None),
+        None => match find_branch(repo, &remote_branch_name, git2::BranchType::Remote)? {
+            Some(branch) => (branch.get().peel_to_commit()?, Some(remote_branch_name)),
+            None => return Ok(SwitchOutcome::NoSuchBranch),
+        },
+    };
+
+    let dirty = dirty_state(repo);
+    if dirty != DirtyState::Clean {
+        return Ok(SwitchOutcome::Dirty(dirty));
+    }
+    if let Some(operation) = ongoing_operation(repo) {
+        return Ok(SwitchOutcome::Operation(operation));
+    }
+
+    repo.checkout_tree(
+        target.as_object(),
+        Some(git2::build::CheckoutBuilder::new().safe()),
+    )?;
+
+    let outcome = match create_from {
+        Some(remote_branch_name) => {
+            let mut branch = repo.branch(branch_name, &target, false)?;
+            branch.set_upstream(Some(&remote_branch_name))?;
+            SwitchOutcome::CreatedTracking(remote_branch_name)
+        }
+        None => SwitchOutcome::CheckedOut,
+    };
+    repo.set_head(&format!("refs/heads/{}", branch_name))?;
+
+    Ok(outcome)
+}
+
+impl Switch {
+    fn print_outcome(&self, outcome: &Result<SwitchOutcome, Error>, palette: &Palette) {
+        let line = match outcome {
+            Ok(SwitchOutcome::AlreadyOnBranch) => palette
+                .clean
+                .paint(format_message_line(&format!("Already on {}", self.branch))),
+            Ok(SwitchOutcome::CheckedOut) => palette
+                .cloning
+                .paint(format_message_line(&format!("Switched to {}", self.branch))),
+            Ok(SwitchOutcome::CreatedTracking(upstream)) => {
+                palette.cloning.paint(format_message_line(&format!(
+                    "Switched to new branch {} tracking {}",
+                    self.branch, upstream
+                )))
+            }
+            Ok(SwitchOutcome::NoSuchBranch) => palette.missing.paint(format_message_line(
+                &format!("No branch {}, left alone", self.branch),
+            )),
+            Ok(SwitchOutcome::Dirty(dirty)) => {
+                println!(
+                    "{}",
+                    format_message_line(&describe_dirty_state(dirty, palette).to_string())
+                );
+                palette.error.paint(format_message_line("Not switched"))
+            }
+            Ok(SwitchOutcome::Operation(operation)) => {
+                println!(
+                    "{}",
+                    format_message_line(&describe_operation(*operation, palette).to_string())
+                );
+                palette.error.paint(format_message_line("Not switched"))
+            }
+            Ok(SwitchOutcome::Missing) => palette
+                .missing
+                .paint(format_message_line("Missing repository")),
+            Err(err) => palette
+                .error
+                .paint(format_message_line(&format!("Error: {}", err))),
+        };
+        println!("{}", line);
+    }
+
+    /// Switch each selected project to the branch, and print the outcome for
+    /// each if `palette` is given.
+    pub fn make_report_and_maybe_print<'ws>(
+        &self,
+        working_dir: &Path,
+        workspace: &'ws Workspace,
+        palette: Option<&Palette>,
+    ) -> Vec<(&'ws Project, Result<SwitchOutcome, Error>)> {
+        workspace
+            .projects
+            .iter()
+            .filter(|project| self.projects.matches(project))
+            .map(|project| {
+                let project_dir = working_dir.join(&project.path);
+                let outcome = if project_dir.exists() {
+                    git2::Repository::open(&project_dir)
+                        .map_err(Error::from)
+                        .and_then(|repo| switch_project(&repo, project, &self.branch))
+                } else {
+                    Ok(SwitchOutcome::Missing)
+                };
+
+                if let Some(palette) = palette {
+                    println!("{}", format_project_header(project, palette));
+                    self.print_outcome(&outcome, palette);
+                }
+
+                (project, outcome)
+            })
+            .collect()
+    }
+}
+
+impl DirectoryCommand for Switch {
+    fn run(
+        &self,
+        working_dir: &Path,
+        workspace: &Workspace,
+        palette: &Palette,
+    ) -> Result<i32, Error> {
+        if let Some(exit_code) = check_path_args(&self.projects, workspace) {
+            return Ok(exit_code);
+        }
+
+        let report = self.make_report_and_maybe_print(working_dir, workspace, Some(palette));
+
+        let moved: Vec<&str> = report
+            .iter()
+            .filter(|(_, outcome)| outcome.as_ref().map(SwitchOutcome::moved).unwrap_or(false))
+            .map(|(project, _)| project.path.as_str())
+            .collect();
+        println!();
+        if moved.is_empty() {
+            println!("No projects switched to {}", self.branch);
+        } else {
+            println!(
+                "Switched {} of {} projects to {}: {}",
+                moved.len(),
+                report.len(),
+                self.branch,
+                moved.join(", ")
+            );
+        }
+
+        Ok(report
+            .iter()
+            .map(|(_, outcome)| match outcome {
+                Ok(outcome) => outcome.exit_code(),
+                Err(_) => exit_codes::INTERNAL_ERROR,
+            })
+            .find(|exit_code| *exit_code != exit_codes::OK)
+            .unwrap_or(exit_codes::OK))
+    }
+}
@@ -1,11 +1,19 @@
 use std::path::Path;
 
+use super::clone::clone_projects;
+use super::common::credentials::Credentials;
+use super::common::filter::ProjectFilter;
 use super::common::DirectoryCommand;
 use super::error::Error;
 use crate::color::palette::Palette;
+use crate::config::data::Project;
 use crate::config::data::Workspace;
 
-pub struct Update {}
+pub struct Update {
+    pub projects: ProjectFilter,
+    pub jobs: usize,
+    pub credentials: Credentials,
+}
 
 impl DirectoryCommand for Update {
     fn run(
@@ -14,9 +22,12 @@ impl DirectoryCommand for Update {
         workspace: &Workspace,
         palette: &Palette,
     ) -> Result<i32, Error> {
-        super::clone::Clone {
-            projects: workspace.projects.iter().map(|p| p.path.clone()).collect(),
-        }
-        .run(working_dir, workspace, palette)
+        let projects: Vec<&Project> = workspace
+            .projects
+            .iter()
+            .filter(|project| self.projects.matches(project))
+            .collect();
+
+        clone_projects(projects, self.jobs, &self.credentials, working_dir, palette)
     }
 }
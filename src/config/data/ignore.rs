@@ -0,0 +1,27 @@
+use regex::Regex;
+
+/// Regular expressions matched against project paths to exclude projects from
+/// the workspace.
+#[derive(Debug, Default)]
+pub struct IgnoreList {
+    pub patterns: Vec<Regex>,
+}
+
+impl IgnoreList {
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.is_match(path))
+    }
+}
+
+impl PartialEq for IgnoreList {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.patterns.len() == rhs.patterns.len()
+            && self
+                .patterns
+                .iter()
+                .zip(rhs.patterns.iter())
+                .all(|(a, b)| a.as_str() == b.as_str())
+    }
+}
+
+impl Eq for IgnoreList {}
@@ -1,8 +1,10 @@
+mod ignore;
 mod project;
 mod remote;
 pub mod user_config;
 mod workspace;
 
+pub use self::ignore::IgnoreList;
 pub use self::project::Project;
 pub use self::remote::MaybeNamedRemote;
 pub use self::remote::Remote;
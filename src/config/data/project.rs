@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 
 use super::Remote;
 use crate::commands::error::Error;
@@ -10,6 +11,10 @@ pub struct Project {
     pub path: String,
     pub main_remote: Remote,
     pub extra_remotes: Vec<Remote>,
+    pub default_branch: Option<String>,
+    pub groups: BTreeSet<String>,
+    /// Whether to initialize and update submodules when cloning.
+    pub submodules: bool,
 }
 
 impl Project {
@@ -55,6 +60,8 @@ impl Project {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use super::Project;
     use super::Remote;
 
@@ -63,6 +70,9 @@ mod tests {
         assert_eq!(
             Project {
                 path: "foo".to_string(),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
                 main_remote: Remote {
                     name: "origin".to_string(),
                     url: "git@github.com:foo/boo.git".to_string(),
@@ -1,4 +1,6 @@
-#[derive(Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+use serde::Serialize;
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Remote {
     pub url: String,
     pub name: String,
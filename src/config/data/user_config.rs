@@ -2,14 +2,41 @@ use super::super::error::ConfigError;
 use crate::color::palette::Palette;
 use ansi_term::Colour;
 use ansi_term::Style;
+use directories::BaseDirs;
 use serde::Deserialize;
+use std::path::PathBuf;
 
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 pub struct UserConfig {
     palette: Option<PaletteConfig>,
+    jobs: Option<usize>,
+    here: Option<bool>,
+    ssh_keys: Option<Vec<PathBuf>>,
 }
 
 impl UserConfig {
+    pub fn jobs(&self) -> Option<usize> {
+        self.jobs
+    }
+
+    pub fn here(&self) -> bool {
+        self.here.unwrap_or(false)
+    }
+
+    /// The configured private SSH keys, with a leading `~` expanded to the
+    /// home directory.
+    pub fn ssh_keys(&self) -> Vec<PathBuf> {
+        let home_dir = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+        self.ssh_keys
+            .iter()
+            .flatten()
+            .map(|path| match (path.strip_prefix("~"), &home_dir) {
+                (Ok(rest), Some(home_dir)) => home_dir.join(rest),
+                _ => path.clone(),
+            })
+            .collect()
+    }
+
     pub fn palette(&self) -> Result<Option<Palette>, ConfigError> {
         match &self.palette {
             Some(p) => Ok(Some(p.make()?)),
@@ -23,24 +50,33 @@ pub struct PaletteConfig {
     pub branch: toml::Value,
     pub clean: toml::Value,
     pub cloning: toml::Value,
+    pub conflict: Option<toml::Value>,
     pub dirty: toml::Value,
     pub error: toml::Value,
+    pub in_progress: Option<toml::Value>,
     pub missing: toml::Value,
     pub repo: toml::Value,
     pub repo_exists: toml::Value,
+    pub staged: Option<toml::Value>,
+    pub stashed: Option<toml::Value>,
 }
 
 impl PaletteConfig {
     fn make(&self) -> Result<Palette, ConfigError> {
+        let default = Palette::default();
         Ok(Palette {
             branch: parse_style(&self.branch)?,
             clean: parse_style(&self.clean)?,
             cloning: parse_style(&self.cloning)?,
+            conflict: parse_optional_style(&self.conflict, default.conflict)?,
             dirty: parse_style(&self.dirty)?,
             error: parse_style(&self.error)?,
+            in_progress: parse_optional_style(&self.in_progress, default.in_progress)?,
             missing: parse_style(&self.missing)?,
             repo: parse_style(&self.repo)?,
             repo_exists: parse_style(&self.repo_exists)?,
+            staged: parse_optional_style(&self.staged, default.staged)?,
+            stashed: parse_optional_style(&self.stashed, default.stashed)?,
         })
     }
 }
@@ -56,6 +92,13 @@ fn parse_style(v: &toml::Value) -> Result<Style, ConfigError> {
     ColourConfig::from(v)?.make_style()
 }
 
+fn parse_optional_style(v: &Option<toml::Value>, default: Style) -> Result<Style, ConfigError> {
+    match v {
+        Some(v) => parse_style(v),
+        None => Ok(default),
+    }
+}
+
 fn in_range_inclusive(value: i64, min: i64, max: i64) -> bool {
     value >= min && value <= max
 }
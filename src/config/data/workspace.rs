@@ -1,16 +1,34 @@
 use std::collections::BTreeSet;
 
+use super::IgnoreList;
 use super::Project;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Workspace {
     pub projects: BTreeSet<Project>,
+    pub ignore: IgnoreList,
+}
+
+impl Workspace {
+    /// Remove the projects matched by `ignore`, and remember `ignore` for
+    /// classifying repositories not in the projects list.
+    pub fn ignoring(self, ignore: IgnoreList) -> Workspace {
+        Workspace {
+            projects: self
+                .projects
+                .into_iter()
+                .filter(|project| !ignore.is_ignored(&project.path))
+                .collect(),
+            ignore,
+        }
+    }
 }
 
 impl<I: IntoIterator<Item = Project>> From<I> for Workspace {
     fn from(projects: I) -> Self {
         Workspace {
             projects: projects.into_iter().collect(),
+            ignore: IgnoreList::default(),
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug)]
 pub enum ConfigError {
     InvalidConfig(String),
@@ -22,3 +24,14 @@ impl PartialEq for ConfigError {
         }
     }
 }
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            InvalidConfig(msg) | InternalError(msg) | SyntaxError(msg) => {
+                write!(formatter, "{}", msg)
+            }
+            OpenFile(e) => fmt::Display::fmt(e, formatter),
+        }
+    }
+}
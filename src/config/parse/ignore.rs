@@ -0,0 +1,103 @@
+use regex::Regex;
+
+use super::super::data::IgnoreList;
+use super::super::error::ConfigError;
+
+/// `line` up to the first `#` that is not escaped as `\#`, with the escapes
+/// replaced by plain `#`.
+fn strip_comment(line: &str) -> String {
+    let mut result = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => break,
+            '\\' => match chars.next() {
+                Some('#') => result.push('#'),
+                Some(next) => {
+                    result.push(c);
+                    result.push(next);
+                }
+                None => result.push(c),
+            },
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+pub fn parse(input: &str) -> Result<IgnoreList, ConfigError> {
+    let nonempty_lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, s)| (i, strip_comment(s)))
+        .map(|(i, s)| (i, s.trim().to_string()))
+        .filter(|(_, s)| !s.is_empty());
+
+    let mut patterns = Vec::new();
+    for (i, line) in nonempty_lines {
+        patterns.push(Regex::new(&line).map_err(|e| {
+            ConfigError::SyntaxError(format!("Invalid regex on line {}: {}", i + 1, e))
+        })?);
+    }
+
+    Ok(IgnoreList { patterns })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn comments_and_blank_lines_are_ignored() {
+        let ignore = parse(
+            "
+            # Work related
+            ^work/
+
+            -work$ # Inline comment
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            ignore
+                .patterns
+                .iter()
+                .map(|p| p.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["^work/", "-work$"]
+        );
+    }
+
+    #[test]
+    fn patterns_match_project_paths() {
+        let ignore = parse("^work/\n-work$\n").unwrap();
+
+        assert!(ignore.is_ignored("work/foo"));
+        assert!(ignore.is_ignored("contrib/foo-work"));
+        assert!(!ignore.is_ignored("contrib/work"));
+        assert!(!ignore.is_ignored("contrib/foo"));
+    }
+
+    #[test]
+    fn escaped_hash_is_part_of_the_pattern() {
+        let ignore = parse("^c\\#/ # C# projects\n\\d\\#\n").unwrap();
+
+        assert_eq!(
+            ignore
+                .patterns
+                .iter()
+                .map(|p| p.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["^c#/", "\\d#"]
+        );
+        assert!(ignore.is_ignored("c#/foo"));
+        assert!(ignore.is_ignored("issue7#"));
+        assert!(!ignore.is_ignored("c/foo"));
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        assert!(parse("^work/(\n").is_err());
+    }
+}
@@ -11,6 +11,48 @@ pub fn parse(input: &str) -> Result<Workspace, ConfigError> {
     input.parse()
 }
 
+/// Serialize a workspace to the legacy `.projects.gws` format, such that
+/// `parse(&serialize(ws))` returns an equal workspace.
+pub fn serialize(workspace: &Workspace) -> String {
+    let path_width = workspace
+        .projects
+        .iter()
+        .map(|project| project.path.len())
+        .max()
+        .unwrap_or(0);
+
+    workspace
+        .projects
+        .iter()
+        .map(|project| {
+            format!(
+                "{: <width$} | {}\n",
+                project.path,
+                serialize_remotes(project),
+                width = path_width
+            )
+        })
+        .collect()
+}
+
+fn serialize_remotes(project: &Project) -> String {
+    let main_remote = if project.main_remote.name == "origin" {
+        project.main_remote.url.clone()
+    } else {
+        format!("{} {}", project.main_remote.url, project.main_remote.name)
+    };
+
+    std::iter::once(main_remote)
+        .chain(
+            project
+                .extra_remotes
+                .iter()
+                .map(|remote| format!("{} {}", remote.url, remote.name)),
+        )
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
 impl FromStr for Workspace {
     type Err = ConfigError;
 
@@ -27,7 +69,7 @@ impl FromStr for Workspace {
             projects.insert(line.parse()?);
         }
 
-        Ok(Workspace { projects })
+        Ok(Workspace::from(projects))
     }
 }
 
@@ -86,6 +128,9 @@ impl FromStr for Project {
 
         Ok(Project {
             path,
+            default_branch: None,
+            groups: BTreeSet::new(),
+            submodules: true,
             main_remote: first_remote,
             extra_remotes,
         })
@@ -113,6 +158,8 @@ impl FromStr for MaybeNamedRemote {
 
 #[cfg(test)]
 mod tests {
+    use super::serialize;
+    use super::BTreeSet;
     use super::Project;
     use super::Remote;
     use super::Workspace;
@@ -139,6 +186,9 @@ mod tests {
             Project::from_str("foo | git@github.com:foo/foo.git"),
             Ok(Project {
                 path: String::from("foo"),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
                 main_remote: Remote {
                     url: String::from("git@github.com:foo/foo.git"),
                     name: String::from("origin"),
@@ -154,6 +204,9 @@ mod tests {
             Project::from_str("foo | git@github.com:foo/foo.git github"),
             Ok(Project {
                 path: String::from("foo"),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
                 main_remote: Remote {
                     url: String::from("git@github.com:foo/foo.git"),
                     name: String::from("github"),
@@ -171,6 +224,9 @@ mod tests {
             ),
             Ok(Project {
                 path: String::from("foo"),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
                 main_remote: Remote {
                     url: String::from("git@github.com:foo/foo.git"),
                     name: String::from("origin"),
@@ -186,6 +242,9 @@ mod tests {
             Project::from_str("foo | git@github.com:foo/foo.git | git@github.com:bar/foo.git"),
             Ok(Project {
                 path: String::from("foo"),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
                 main_remote: Remote {
                     url: String::from("git@github.com:foo/foo.git"),
                     name: String::from("origin"),
@@ -210,6 +269,9 @@ mod tests {
             Ok(
                 Project {
                     path: String::from("foo"),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         url: String::from("git@github.com:foo/foo.git"),
                         name: String::from("github-foo"),
@@ -232,6 +294,9 @@ mod tests {
             Ok(
                 Project {
                     path: String::from("foo"),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         url: String::from("git@github.com:foo/foo.git"),
                         name: String::from("github-foo"),
@@ -289,6 +354,9 @@ mod tests {
             Ok(Workspace::from(vec![
                 Project {
                     path: "foo/bar".to_string(),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         name: "origin".to_string(),
                         url: "https://github.com/foo/bar.git".to_string(),
@@ -297,6 +365,9 @@ mod tests {
                 },
                 Project {
                     path: "boo".to_string(),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         name: "origin".to_string(),
                         url: "git@github.com:foo/boo.git".to_string(),
@@ -314,6 +385,9 @@ mod tests {
                 },
                 Project {
                     path: "moo".to_string(),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         name: "origin".to_string(),
                         url: "git@github.com:foo/moo.git".to_string(),
@@ -323,4 +397,43 @@ mod tests {
             ],))
         )
     }
+
+    #[test]
+    fn serialized_workspace_is_parsed_to_equal_workspace() {
+        let workspace = Workspace::from(vec![
+            Project {
+                path: "foo/bar".to_string(),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
+                main_remote: Remote {
+                    name: "origin".to_string(),
+                    url: "https://github.com/foo/bar.git".to_string(),
+                },
+                extra_remotes: vec![],
+            },
+            Project {
+                path: "boo".to_string(),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
+                main_remote: Remote {
+                    name: "github".to_string(),
+                    url: "git@github.com:foo/boo.git".to_string(),
+                },
+                extra_remotes: vec![
+                    Remote {
+                        name: "myone".to_string(),
+                        url: "http://coool".to_string(),
+                    },
+                    Remote {
+                        name: "upstream".to_string(),
+                        url: "testurl".to_string(),
+                    },
+                ],
+            },
+        ]);
+
+        assert_eq!(serialize(&workspace).parse(), Ok(workspace));
+    }
 }
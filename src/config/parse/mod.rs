@@ -1 +1,3 @@
+pub mod ignore;
 pub mod legacy;
+pub mod toml_workspace;
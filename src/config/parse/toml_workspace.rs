@@ -0,0 +1,261 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use super::super::data::Project;
+use super::super::data::Remote;
+use super::super::data::Workspace;
+use super::super::error::ConfigError;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceTable {
+    #[serde(default)]
+    projects: BTreeMap<String, ProjectTable>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectTable {
+    #[serde(default)]
+    default_branch: Option<String>,
+    #[serde(default)]
+    groups: BTreeSet<String>,
+    #[serde(default = "default_submodules")]
+    submodules: bool,
+    remotes: Vec<RemoteTable>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RemoteTable {
+    name: String,
+    url: String,
+}
+
+fn default_submodules() -> bool {
+    true
+}
+
+impl ProjectTable {
+    fn into_project(self, path: String) -> Result<Project, ConfigError> {
+        let mut remotes = self.remotes.into_iter().map(|remote| Remote {
+            name: remote.name,
+            url: remote.url,
+        });
+
+        let main_remote = remotes.next().ok_or_else(|| {
+            ConfigError::InvalidConfig(format!(
+                "At least one remote is required for project {}",
+                path
+            ))
+        })?;
+
+        Ok(Project {
+            path,
+            main_remote,
+            extra_remotes: remotes.collect(),
+            default_branch: self.default_branch,
+            groups: self.groups,
+            submodules: self.submodules,
+        })
+    }
+}
+
+/// Parse a workspace in the TOML `.projects.toml` format. The first remote
+/// listed for each project is its main remote.
+pub fn parse(input: &str) -> Result<Workspace, ConfigError> {
+    let table: WorkspaceTable = toml::from_str(input).map_err(|e| {
+        ConfigError::SyntaxError(match e.line_col() {
+            Some((line, col)) => format!(
+                "TOML syntax error at line {}, column {}: {}",
+                line + 1,
+                col + 1,
+                e
+            ),
+            None => format!("TOML syntax error: {}", e),
+        })
+    })?;
+
+    let mut projects = BTreeSet::new();
+    for (path, project) in table.projects {
+        projects.insert(project.into_project(path)?);
+    }
+    Ok(Workspace::from(projects))
+}
+
+fn quote(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+fn serialize_project(project: &Project) -> String {
+    let mut lines = vec![format!("[projects.{}]", quote(&project.path))];
+
+    if let Some(default_branch) = &project.default_branch {
+        lines.push(format!("default_branch = {}", quote(default_branch)));
+    }
+    if !project.groups.is_empty() {
+        lines.push(format!(
+            "groups = [{}]",
+            project
+                .groups
+                .iter()
+                .map(|group| quote(group))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+    if project.submodules != default_submodules() {
+        lines.push(format!("submodules = {}", project.submodules));
+    }
+
+    lines.push("remotes = [".to_string());
+    for remote in project.remotes() {
+        lines.push(format!(
+            "    {{ name = {}, url = {} }},",
+            quote(&remote.name),
+            quote(&remote.url)
+        ));
+    }
+    lines.push("]\n".to_string());
+
+    lines.join("\n")
+}
+
+/// Serialize a workspace to the TOML `.projects.toml` format, such that
+/// `parse(&serialize(ws))` returns an equal workspace.
+pub fn serialize(workspace: &Workspace) -> String {
+    workspace
+        .projects
+        .iter()
+        .map(serialize_project)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::super::super::data::Project;
+    use super::super::super::data::Remote;
+    use super::super::super::data::Workspace;
+    use super::super::super::error::ConfigError;
+    use super::parse;
+    use super::serialize;
+
+    fn example_workspace() -> Workspace {
+        Workspace::from(vec![
+            Project {
+                path: "foo/bar".to_string(),
+                default_branch: None,
+                groups: BTreeSet::new(),
+                submodules: true,
+                main_remote: Remote {
+                    name: "origin".to_string(),
+                    url: "https://github.com/foo/bar.git".to_string(),
+                },
+                extra_remotes: vec![],
+            },
+            Project {
+                path: "contrib/gws".to_string(),
+                default_branch: Some("master".to_string()),
+                groups: vec!["contrib".to_string(), "rust".to_string()]
+                    .into_iter()
+                    .collect(),
+                submodules: false,
+                main_remote: Remote {
+                    name: "github".to_string(),
+                    url: "git@github.com:emlun/gws2.git".to_string(),
+                },
+                extra_remotes: vec![
+                    Remote {
+                        name: "upstream".to_string(),
+                        url: "https://github.com/StreakyCobra/gws.git".to_string(),
+                    },
+                    Remote {
+                        name: "fork".to_string(),
+                        url: "https://example.org/gws.git".to_string(),
+                    },
+                ],
+            },
+        ])
+    }
+
+    #[test]
+    fn projects_are_parsed_correctly() {
+        let input = r#"
+[projects."foo/bar"]
+remotes = [{ name = "origin", url = "https://github.com/foo/bar.git" }]
+
+[projects."contrib/gws"]
+default_branch = "master"
+groups = ["rust", "contrib"]
+submodules = false
+
+[[projects."contrib/gws".remotes]]
+name = "github"
+url = "git@github.com:emlun/gws2.git"
+
+[[projects."contrib/gws".remotes]]
+name = "upstream"
+url = "https://github.com/StreakyCobra/gws.git"
+
+[[projects."contrib/gws".remotes]]
+name = "fork"
+url = "https://example.org/gws.git"
+"#;
+        assert_eq!(parse(input), Ok(example_workspace()));
+    }
+
+    #[test]
+    fn empty_file_is_an_empty_workspace() {
+        assert_eq!(parse(""), Ok(Workspace::from(vec![])));
+    }
+
+    #[test]
+    fn project_without_remotes_is_invalid() {
+        assert_eq!(
+            parse("[projects.foo]\nremotes = []\n"),
+            Err(ConfigError::InvalidConfig(
+                "At least one remote is required for project foo".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_an_error() {
+        assert!(matches!(
+            parse("[projects.foo]\nremote = \"https://example.org/foo.git\"\n"),
+            Err(ConfigError::SyntaxError(_))
+        ));
+    }
+
+    #[test]
+    fn serialize_writes_one_table_per_project() {
+        assert_eq!(
+            serialize(&example_workspace()),
+            r#"[projects."contrib/gws"]
+default_branch = "master"
+groups = ["contrib", "rust"]
+submodules = false
+remotes = [
+    { name = "github", url = "git@github.com:emlun/gws2.git" },
+    { name = "upstream", url = "https://github.com/StreakyCobra/gws.git" },
+    { name = "fork", url = "https://example.org/gws.git" },
+]
+
+[projects."foo/bar"]
+remotes = [
+    { name = "origin", url = "https://github.com/foo/bar.git" },
+]
+"#
+        );
+    }
+
+    #[test]
+    fn serialize_roundtrips() {
+        let ws = example_workspace();
+        assert_eq!(parse(&serialize(&ws)), Ok(ws));
+    }
+}
@@ -3,18 +3,40 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+use super::data::IgnoreList;
 use super::data::Workspace;
 use super::error::ConfigError;
+use super::parse::ignore;
 use super::parse::legacy;
+use super::parse::toml_workspace;
 
+/// Read a workspace file, in the TOML format if its extension is `.toml` and
+/// in the legacy format otherwise.
 pub fn read_workspace_file<P: AsRef<Path>>(file_path: P) -> Result<Workspace, ConfigError> {
     let mut contents: String = String::new();
 
+    let is_toml = file_path.as_ref().extension() == Some("toml".as_ref());
     let mut file = File::open(file_path).map_err(ConfigError::OpenFile)?;
 
     file.read_to_string(&mut contents)
         .map_err(ConfigError::OpenFile)
-        .and_then(|_| legacy::parse(&contents))
+        .and_then(|_| {
+            if is_toml {
+                toml_workspace::parse(&contents)
+            } else {
+                legacy::parse(&contents)
+            }
+        })
+}
+
+pub fn read_ignore_file<P: AsRef<Path>>(file_path: P) -> Result<IgnoreList, ConfigError> {
+    let mut contents: String = String::new();
+
+    let mut file = File::open(file_path).map_err(ConfigError::OpenFile)?;
+
+    file.read_to_string(&mut contents)
+        .map_err(ConfigError::OpenFile)
+        .and_then(|_| ignore::parse(&contents))
 }
 
 fn read_config_toml(content: &str) -> Result<UserConfig, toml::de::Error> {
@@ -47,6 +69,7 @@ pub fn read_config_file<P: AsRef<Path>>(file_path: P) -> Result<UserConfig, Conf
 #[cfg(test)]
 mod tests {
     use ansi_term::Colour;
+    use std::collections::BTreeSet;
     use std::path::Path;
 
     use super::super::data::Project;
@@ -65,6 +88,9 @@ mod tests {
             Ok(Workspace::from(vec![
                 Project {
                     path: "foo/bar".to_string(),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         name: "origin".to_string(),
                         url: "https://github.com/foo/bar.git".to_string(),
@@ -73,6 +99,9 @@ mod tests {
                 },
                 Project {
                     path: "boo".to_string(),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         name: "origin".to_string(),
                         url: "git@github.com:foo/boo.git".to_string(),
@@ -90,6 +119,9 @@ mod tests {
                 },
                 Project {
                     path: "moo".to_string(),
+                    default_branch: None,
+                    groups: BTreeSet::new(),
+                    submodules: true,
                     main_remote: Remote {
                         name: "origin".to_string(),
                         url: "git@github.com:foo/moo.git".to_string(),
@@ -112,17 +144,22 @@ mod tests {
             missing = [255, 0x64, 0x2b]
             repo = "green"
             repo_exists = 10
+            stashed = 240
         "##;
 
         let expected = Palette {
             branch: Colour::Fixed(13).normal(),
             clean: Colour::Fixed(9).normal(),
             cloning: Colour::Fixed(14).normal(),
+            conflict: Colour::Fixed(1).normal(),
             dirty: Colour::Fixed(9).normal(),
             error: Colour::RGB(0xff, 0x64, 0x2b).normal(),
+            in_progress: Colour::Fixed(208).normal(),
             missing: Colour::RGB(0xff, 0x64, 0x2b).normal(),
             repo: Colour::Green.normal(),
             repo_exists: Colour::Fixed(10).normal(),
+            staged: Colour::Fixed(11).normal(),
+            stashed: Colour::Fixed(240).normal(),
         };
 
         let config = read_config_toml(&config_content)?;
@@ -130,4 +167,31 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn jobs_is_parsed_correctly() -> Result<(), toml::de::Error> {
+        assert_eq!(read_config_toml("jobs = 8")?.jobs(), Some(8));
+        assert_eq!(read_config_toml("")?.jobs(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn here_is_parsed_correctly() -> Result<(), toml::de::Error> {
+        assert!(read_config_toml("here = true")?.here());
+        assert!(!read_config_toml("here = false")?.here());
+        assert!(!read_config_toml("")?.here());
+        Ok(())
+    }
+
+    #[test]
+    fn ssh_keys_are_parsed_correctly() -> Result<(), toml::de::Error> {
+        let config = read_config_toml(r#"ssh_keys = ["/keys/id_ed25519", "~/.ssh/id_rsa"]"#)?;
+        let keys = config.ssh_keys();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0], std::path::Path::new("/keys/id_ed25519"));
+        assert!(keys[1].ends_with(".ssh/id_rsa"));
+        assert!(!keys[1].starts_with("~"));
+        assert!(read_config_toml("")?.ssh_keys().is_empty());
+        Ok(())
+    }
 }
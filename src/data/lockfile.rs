@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::crate_info::crate_name;
+
+/// The state of a workspace as recorded by `gws freeze`, keyed by project
+/// path.
+#[derive(Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub projects: BTreeMap<String, LockedProject>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockedProject {
+    /// The branch that was checked out, or `None` if `HEAD` was detached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// The full hex ID of the commit `HEAD` pointed to.
+    pub commit: String,
+}
+
+pub fn parse(input: &str) -> Result<Lockfile, toml::de::Error> {
+    toml::from_str(input)
+}
+
+pub fn serialize(lockfile: &Lockfile) -> String {
+    format!(
+        "# Generated by {} freeze\n\n{}",
+        crate_name(),
+        toml::to_string(lockfile).expect("Lockfile should always serialize to TOML")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use super::serialize;
+    use super::LockedProject;
+    use super::Lockfile;
+
+    fn example_lockfile() -> Lockfile {
+        Lockfile {
+            projects: vec![
+                (
+                    "contrib/gws".to_string(),
+                    LockedProject {
+                        branch: Some("master".to_string()),
+                        commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+                    },
+                ),
+                (
+                    "foo".to_string(),
+                    LockedProject {
+                        branch: None,
+                        commit: "89abcdef0123456789abcdef0123456789abcdef".to_string(),
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    #[test]
+    fn serialize_writes_one_table_per_project() {
+        assert_eq!(
+            serialize(&example_lockfile()),
+            r#"# Generated by gws freeze
+
+[projects."contrib/gws"]
+branch = "master"
+commit = "0123456789abcdef0123456789abcdef01234567"
+
+[projects.foo]
+commit = "89abcdef0123456789abcdef0123456789abcdef"
+"#
+        );
+    }
+
+    #[test]
+    fn serialize_roundtrips() {
+        assert_eq!(
+            parse(&serialize(&example_lockfile())),
+            Ok(example_lockfile())
+        );
+    }
+}
@@ -1 +1,3 @@
+pub mod lockfile;
+pub mod serialize;
 pub mod status;
@@ -0,0 +1,189 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+use super::status::WorkspaceStatus;
+use crate::commands::error::Error;
+use crate::config::data::Project;
+use crate::config::data::Remote;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SerializedFormat {
+    /// A single JSON document with a `projects` array
+    Json,
+    /// One JSON document per project, one per line
+    Ndjson,
+}
+
+#[derive(Serialize)]
+struct WorkspaceReport<'a, R> {
+    projects: Vec<ProjectReport<'a, R>>,
+}
+
+#[derive(Serialize)]
+struct ProjectReport<'a, R> {
+    path: &'a str,
+    remotes: Vec<&'a Remote>,
+    #[serde(flatten)]
+    result: ProjectResult<'a, R>,
+}
+
+/// The fields of the command's report, or `branches` and `head` as `null` if
+/// the command failed, followed by the `error`.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ProjectResult<'a, R> {
+    Ok {
+        #[serde(flatten)]
+        report: &'a R,
+        error: Option<ErrorReport>,
+    },
+    Err {
+        branches: Option<()>,
+        head: Option<()>,
+        error: ErrorReport,
+    },
+}
+
+#[derive(Serialize)]
+struct ErrorReport {
+    kind: &'static str,
+    message: String,
+}
+
+fn error_kind(error: &Error) -> &'static str {
+    match error {
+        Error::Git2(_) => "git",
+        Error::Io(_) => "io",
+        Error::NoBranchNameFound => "no_branch_name_found",
+        Error::RepositoryMissing => "repository_missing",
+    }
+}
+
+impl<'a, R> ProjectReport<'a, R> {
+    fn from(project: &'a Project, result: &'a Result<R, Error>) -> Self {
+        ProjectReport {
+            path: &project.path,
+            remotes: project.remotes(),
+            result: match result {
+                Ok(report) => ProjectResult::Ok {
+                    report,
+                    error: None,
+                },
+                Err(error) => ProjectResult::Err {
+                    branches: None,
+                    head: None,
+                    error: ErrorReport {
+                        kind: error_kind(error),
+                        message: error.to_string(),
+                    },
+                },
+            },
+        }
+    }
+}
+
+pub fn write_workspace_status<W: Write, R: Serialize>(
+    out: &mut W,
+    status: &WorkspaceStatus<R>,
+    format: SerializedFormat,
+) -> Result<(), Error> {
+    let projects = status
+        .iter()
+        .map(|(project, project_status)| ProjectReport::from(project, project_status));
+
+    match format {
+        SerializedFormat::Json => {
+            serde_json::to_writer_pretty(
+                &mut *out,
+                &WorkspaceReport {
+                    projects: projects.collect(),
+                },
+            )
+            .map_err(std::io::Error::from)?;
+            writeln!(out)?;
+        }
+        SerializedFormat::Ndjson => {
+            for project in projects {
+                serde_json::to_writer(&mut *out, &project).map_err(std::io::Error::from)?;
+                writeln!(out)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::collections::BTreeSet;
+
+    use super::write_workspace_status;
+    use super::SerializedFormat;
+    use crate::commands::error::Error;
+    use crate::config::data::Project;
+    use crate::config::data::Remote;
+    use crate::data::status::BranchStatus;
+    use crate::data::status::DirtyState;
+    use crate::data::status::Head;
+    use crate::data::status::RepositoryStatus;
+    use crate::data::status::WorkspaceStatus;
+
+    fn project(path: &str) -> Project {
+        Project {
+            path: path.to_string(),
+            default_branch: None,
+            groups: BTreeSet::new(),
+            submodules: true,
+            main_remote: Remote {
+                name: "origin".to_string(),
+                url: format!("git@github.com:foo/{}.git", path),
+            },
+            extra_remotes: vec![],
+        }
+    }
+
+    #[test]
+    fn ndjson_has_one_line_per_project() {
+        let foo = project("foo");
+        let bar = project("bar");
+        let mut status: WorkspaceStatus = BTreeMap::new();
+        status.insert(
+            &foo,
+            Ok(RepositoryStatus {
+                branches: vec![BranchStatus {
+                    name: "master".to_string(),
+                    upstream_name: Some("origin/master".to_string()),
+                    dirty: DirtyState::UnstagedChanges,
+                    operation: None,
+                    stashes: 0,
+                    is_head: true,
+                    in_sync: Some(true),
+                    ahead: 0,
+                    behind: 0,
+                    upstream_fetched: false,
+                    fast_forwarded: false,
+                    rebased: false,
+                }]
+                .into_iter()
+                .collect(),
+                head: Head::Branch("master".to_string()),
+            }),
+        );
+        status.insert(&bar, Err(Error::RepositoryMissing));
+
+        let mut out: Vec<u8> = Vec::new();
+        write_workspace_status(&mut out, &status, SerializedFormat::Ndjson).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"path":"bar","remotes":[{"url":"git@github.com:foo/bar.git","name":"origin"}],"branches":null,"head":null,"error":{"kind":"repository_missing","message":"Repository missing"}}"#,
+                "\n",
+                r#"{"path":"foo","remotes":[{"url":"git@github.com:foo/foo.git","name":"origin"}],"branches":[{"name":"master","upstream_name":"origin/master","dirty":"unstaged_changes","operation":null,"stashes":0,"is_head":true,"in_sync":true,"ahead":0,"behind":0,"upstream_fetched":false,"fast_forwarded":false,"rebased":false}],"head":{"branch":"master"},"error":null}"#,
+                "\n",
+            )
+        );
+    }
+}
@@ -7,12 +7,79 @@ use git2::Commit;
 use git2::Reference;
 use git2::Repository;
 use git2::Status;
+use serde::Serialize;
 
 use crate::commands::error::Error;
 use crate::config::data::Project;
 
-pub type WorkspaceStatus<'proj> = BTreeMap<&'proj Project, Result<RepositoryStatus, Error>>;
-pub type RepositoryStatus = BTreeSet<BranchStatus>;
+/// The result of a command in each project, by default just the status.
+pub type WorkspaceStatus<'proj, R = RepositoryStatus> = BTreeMap<&'proj Project, Result<R, Error>>;
+
+/// The status of each local branch in a repository, and what `HEAD` points to.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct RepositoryStatus {
+    pub branches: BTreeSet<BranchStatus>,
+    pub head: Head,
+}
+
+impl RepositoryStatus {
+    pub fn iter(&self) -> std::collections::btree_set::Iter<'_, BranchStatus> {
+        self.branches.iter()
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Head {
+    /// A local branch is checked out, and its entry in `branches` has the
+    /// state of the working tree.
+    Branch(String),
+    /// No branch is checked out, for example in the middle of a rebase.
+    Detached(DetachedHead),
+    /// The checked out branch has no commits yet.
+    Unborn,
+}
+
+/// A commit checked out without a branch, and the state of the working tree.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct DetachedHead {
+    /// The full hex ID of the commit
+    pub commit: String,
+    pub dirty: DirtyState,
+    pub operation: Option<OngoingOperation>,
+    pub stashes: usize,
+}
+
+impl Head {
+    /// Whether there is nothing to report about a detached `HEAD`. A checked
+    /// out branch is reported through its entry in `branches` instead.
+    pub fn is_clean(&self) -> bool {
+        match self {
+            Head::Detached(detached) => {
+                detached.dirty == DirtyState::Clean && detached.operation.is_none()
+            }
+            Head::Branch(_) | Head::Unborn => true,
+        }
+    }
+}
+
+impl IntoIterator for RepositoryStatus {
+    type Item = BranchStatus;
+    type IntoIter = std::collections::btree_set::IntoIter<BranchStatus>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.branches.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RepositoryStatus {
+    type Item = &'a BranchStatus;
+    type IntoIter = std::collections::btree_set::Iter<'a, BranchStatus>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.branches.iter()
+    }
+}
 
 pub trait BranchMethods<'repo> {
     fn branch_name(&self) -> Result<&str, Error>;
@@ -21,14 +88,25 @@ pub trait BranchMethods<'repo> {
 }
 
 trait RepositoryMethods {
-    fn any_file(&self, pred: fn(&Status) -> bool) -> bool;
+    fn ahead_behind_upstream(&self, branch: &Branch) -> Result<(usize, usize), Error>;
+    fn combined_file_status(&self) -> Status;
+    fn configured_upstream_name(&self, branch: &Branch) -> Option<String>;
+    fn dirty_state(&self) -> DirtyState;
+    fn head_state(
+        &self,
+        dirty: DirtyState,
+        operation: Option<OngoingOperation>,
+        stashes: usize,
+    ) -> Result<Head, Error>;
     fn is_head(&self, branch: &Branch) -> Result<bool, Error>;
-    fn project_status(&self, project: &Project) -> Result<RepositoryStatus, Error>;
+    fn ongoing_operation(&self) -> Option<OngoingOperation>;
+    fn repository_status(&self) -> Result<RepositoryStatus, Error>;
+    fn stash_count(&self) -> usize;
 }
 
 trait StatusMethods {
-    fn is_dirty(&self) -> bool;
-    fn is_modified(&self) -> bool;
+    fn is_staged(&self) -> bool;
+    fn is_unstaged(&self) -> bool;
     fn is_untracked(&self) -> bool;
 }
 
@@ -59,19 +137,107 @@ impl<'repo> BranchMethods<'repo> for Branch<'repo> {
 }
 
 pub fn project_status(
-    project: &Project,
+    _project: &Project,
     repository: &git2::Repository,
 ) -> Result<RepositoryStatus, Error> {
-    repository.project_status(project)
+    repository.repository_status()
+}
+
+/// The status of a repository regardless of whether it is in the projects
+/// list.
+pub fn repository_status(repository: &git2::Repository) -> Result<RepositoryStatus, Error> {
+    repository.repository_status()
+}
+
+/// The state of the working tree and index of a repository.
+pub fn dirty_state(repository: &git2::Repository) -> DirtyState {
+    repository.dirty_state()
+}
+
+/// The number of stash entries in a repository.
+pub fn stash_count(repository: &git2::Repository) -> usize {
+    repository.stash_count()
+}
+
+/// The operation in progress in a repository, if any.
+pub fn ongoing_operation(repository: &git2::Repository) -> Option<OngoingOperation> {
+    repository.ongoing_operation()
 }
 
 impl RepositoryMethods for Repository {
-    fn any_file(&self, pred: fn(&Status) -> bool) -> bool {
+    fn ahead_behind_upstream(&self, branch: &Branch) -> Result<(usize, usize), Error> {
+        match branch.upstream().ok() {
+            None => Ok((0, 0)),
+            Some(upstream) => Ok(self.graph_ahead_behind(
+                branch.get().peel_to_commit()?.id(),
+                upstream.get().peel_to_commit()?.id(),
+            )?),
+        }
+    }
+
+    fn combined_file_status(&self) -> Status {
         self.statuses(None)
             .iter()
             .flat_map(|ss| ss.iter())
             .map(|s| s.status())
-            .any(|s| pred(&s))
+            .fold(Status::CURRENT, |acc, s| acc | s)
+    }
+
+    /// The upstream configured for `branch`, even if the remote-tracking branch
+    /// no longer exists, for example after a pruning fetch.
+    fn configured_upstream_name(&self, branch: &Branch) -> Option<String> {
+        let upstream_ref = self.branch_upstream_name(branch.get().name()?).ok()?;
+        let upstream_ref = upstream_ref.as_str()?;
+        Some(
+            upstream_ref
+                .strip_prefix("refs/remotes/")
+                .or_else(|| upstream_ref.strip_prefix("refs/heads/"))
+                .unwrap_or(upstream_ref)
+                .to_string(),
+        )
+    }
+
+    fn dirty_state(&self) -> DirtyState {
+        let status = self.combined_file_status();
+
+        if status.is_conflicted() {
+            DirtyState::Conflicts
+        } else if status.is_staged() && status.is_unstaged() {
+            DirtyState::StagedAndUnstagedChanges
+        } else if status.is_staged() {
+            DirtyState::StagedChanges
+        } else if status.is_unstaged() {
+            DirtyState::UnstagedChanges
+        } else if status.is_untracked() {
+            DirtyState::UntrackedFiles
+        } else {
+            DirtyState::Clean
+        }
+    }
+
+    /// What `HEAD` points to, with the given state of the working tree if it
+    /// is detached.
+    fn head_state(
+        &self,
+        dirty: DirtyState,
+        operation: Option<OngoingOperation>,
+        stashes: usize,
+    ) -> Result<Head, Error> {
+        match self.head() {
+            Ok(head) if self.head_detached()? => Ok(Head::Detached(DetachedHead {
+                commit: head.peel_to_commit()?.id().to_string(),
+                dirty,
+                operation,
+                stashes,
+            })),
+            Ok(head) => Ok(Head::Branch(
+                head.shorthand()
+                    .ok_or(Error::NoBranchNameFound)?
+                    .to_string(),
+            )),
+            Err(err) if err.code() == git2::ErrorCode::UnbornBranch => Ok(Head::Unborn),
+            Err(err) => Err(err.into()),
+        }
     }
 
     fn is_head(&self, branch: &Branch) -> Result<bool, Error> {
@@ -80,16 +246,37 @@ impl RepositoryMethods for Repository {
         Ok(head.name() == br.name())
     }
 
-    fn project_status(&self, _project: &Project) -> Result<RepositoryStatus, Error> {
-        let dirty_status = if self.any_file(StatusMethods::is_dirty) {
-            if self.any_file(StatusMethods::is_modified) {
-                DirtyState::UncommittedChanges
-            } else {
-                DirtyState::UntrackedFiles
+    fn stash_count(&self) -> usize {
+        self.reflog("refs/stash")
+            .map(|reflog| reflog.len())
+            .unwrap_or(0)
+    }
+
+    fn ongoing_operation(&self) -> Option<OngoingOperation> {
+        use git2::RepositoryState;
+
+        match self.state() {
+            RepositoryState::Clean => None,
+            RepositoryState::Merge => Some(OngoingOperation::Merge),
+            RepositoryState::Revert | RepositoryState::RevertSequence => {
+                Some(OngoingOperation::Revert)
             }
-        } else {
-            DirtyState::Clean
-        };
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(OngoingOperation::CherryPick)
+            }
+            RepositoryState::Bisect => Some(OngoingOperation::Bisect),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(OngoingOperation::Rebase),
+            RepositoryState::ApplyMailbox => Some(OngoingOperation::ApplyMailbox),
+        }
+    }
+
+    fn repository_status(&self) -> Result<RepositoryStatus, Error> {
+        let dirty_status = self.dirty_state();
+        let operation = self.ongoing_operation();
+        let stashes = self.stash_count();
 
         let branch_stati = self
             .branches(Some(BranchType::Local))
@@ -100,40 +287,53 @@ impl RepositoryMethods for Repository {
 
                 let is_head_branch = self.is_head(&b).unwrap();
                 let is_in_sync = b.is_up_to_date_with_upstream().unwrap();
+                let (ahead, behind) = self.ahead_behind_upstream(&b)?;
 
-                BranchStatus {
+                Ok(BranchStatus {
                     name: b_name.to_string(),
                     upstream_name: match b.upstream_name() {
                         Ok(Some(s)) => Some(s),
-                        _ => None,
+                        _ => self.configured_upstream_name(&b),
                     },
                     dirty: if is_head_branch {
                         dirty_status.clone()
                     } else {
                         DirtyState::Clean
                     },
+                    operation: if is_head_branch { operation } else { None },
+                    stashes: if is_head_branch { stashes } else { 0 },
                     is_head: is_head_branch,
                     in_sync: is_in_sync,
+                    ahead,
+                    behind,
                     upstream_fetched: false,
                     fast_forwarded: false,
-                }
+                    rebased: false,
+                })
             });
 
-        Ok(branch_stati.collect())
+        let branches = branch_stati.collect::<Result<_, Error>>()?;
+        let head = self.head_state(dirty_status, operation, stashes)?;
+
+        Ok(RepositoryStatus { branches, head })
     }
 }
 
 impl StatusMethods for Status {
-    fn is_dirty(&self) -> bool {
-        let mut acceptable_flags = Status::CURRENT;
-        acceptable_flags.insert(Status::IGNORED);
-        let acceptable_flags = acceptable_flags;
-
-        acceptable_flags.bits() & self.bits() != self.bits()
+    fn is_staged(&self) -> bool {
+        self.intersects(
+            Status::INDEX_NEW
+                | Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        )
     }
 
-    fn is_modified(&self) -> bool {
-        self.is_dirty() && !self.is_untracked()
+    fn is_unstaged(&self) -> bool {
+        self.intersects(
+            Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+        )
     }
 
     fn is_untracked(&self) -> bool {
@@ -141,29 +341,153 @@ impl StatusMethods for Status {
     }
 }
 
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct BranchStatus {
     pub name: String,
     pub upstream_name: Option<String>,
     pub dirty: DirtyState,
+    /// Operation in progress in the repository, only set for the HEAD branch
+    pub operation: Option<OngoingOperation>,
+    /// Number of stash entries in the repository, only set for the HEAD branch
+    pub stashes: usize,
     pub is_head: bool,
     pub in_sync: Option<bool>,
+    /// Number of commits on the branch that are not on its upstream
+    pub ahead: usize,
+    /// Number of commits on the upstream that are not on the branch
+    pub behind: usize,
     pub upstream_fetched: bool,
     pub fast_forwarded: bool,
+    pub rebased: bool,
 }
 
 impl BranchStatus {
     pub fn is_clean(&self) -> bool {
-        (self.dirty == DirtyState::Clean || !self.is_head)
+        ((self.dirty == DirtyState::Clean && self.operation.is_none()) || !self.is_head)
             && self.in_sync.unwrap_or(true)
+            && !self.upstream_gone()
             && !self.upstream_fetched
             && !self.fast_forwarded
+            && !self.rebased
+    }
+
+    /// Whether the branch has an upstream configured, but the upstream branch
+    /// no longer exists.
+    pub fn upstream_gone(&self) -> bool {
+        self.upstream_name.is_some() && self.in_sync.is_none()
+    }
+}
+
+/// A failure to fetch one of a project's remotes.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct FetchError {
+    pub remote: String,
+    pub kind: FetchErrorKind,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchErrorKind {
+    /// The remote rejected the credentials, or none were available.
+    Auth,
+    /// The remote could not be reached.
+    Network,
+    /// The remote is in the projects list but not in the repository.
+    MissingRemote,
+    Other,
+}
+
+impl FetchError {
+    pub fn from_git2(remote: &str, error: &git2::Error) -> FetchError {
+        use git2::ErrorClass;
+        use git2::ErrorCode;
+
+        let kind = if error.code() == ErrorCode::Auth {
+            FetchErrorKind::Auth
+        } else if matches!(
+            error.class(),
+            ErrorClass::Net | ErrorClass::Ssh | ErrorClass::Http | ErrorClass::Ssl
+        ) {
+            FetchErrorKind::Network
+        } else {
+            FetchErrorKind::Other
+        };
+
+        FetchError {
+            remote: remote.to_string(),
+            kind,
+            message: error.message().to_string(),
+        }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+/// A failure to update a branch, which was left unchanged.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct BranchError {
+    pub branch: String,
+    pub message: String,
+}
+
+/// A branch pushed, or to be pushed, to its upstream.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct PushResult {
+    pub branch: String,
+    pub upstream_name: String,
+    /// Number of commits on the branch that were not on its upstream
+    pub commits: usize,
+    pub outcome: PushOutcome,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushOutcome {
+    Pushed,
+    /// Not pushed because of a dry run.
+    WouldPush,
+    Failed(String),
+}
+
+/// A branch created, or to be created, by `gws branch`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct BranchCreation {
+    pub branch: String,
+    /// The name of the branch or commit the new branch starts from
+    pub start_point: String,
+    pub upstream_name: Option<String>,
+    /// The upstream that was asked for but not set, because the remote
+    /// branch doesn't exist
+    pub missing_upstream: Option<String>,
+    pub outcome: BranchCreationOutcome,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchCreationOutcome {
+    Created,
+    /// Not created because a branch with the same name already exists.
+    AlreadyExists,
+    Failed(String),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DirtyState {
     Clean,
-    UncommittedChanges,
+    Conflicts,
+    StagedChanges,
+    StagedAndUnstagedChanges,
+    UnstagedChanges,
     UntrackedFiles,
 }
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OngoingOperation {
+    ApplyMailbox,
+    Bisect,
+    CherryPick,
+    Merge,
+    Rebase,
+    Revert,
+}
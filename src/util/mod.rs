@@ -1 +1,2 @@
 pub mod iter;
+pub mod parallel;
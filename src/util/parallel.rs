@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+
+/// Apply `f` to each of `items` using up to `jobs` threads, and pass the
+/// results to `consume` in the same order as `items`.
+///
+/// `consume` is called on the current thread as soon as all results before it
+/// are available, so output can be printed while later items are still being
+/// processed. With `jobs <= 1`, no threads are spawned.
+pub fn for_each_in_order<T, R, F, C>(items: Vec<T>, jobs: usize, f: F, mut consume: C)
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    C: FnMut(R),
+{
+    if jobs <= 1 || items.len() <= 1 {
+        for item in &items {
+            consume(f(item));
+        }
+        return;
+    }
+
+    let next_index = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel::<(usize, R)>();
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(items.len()) {
+            let sender = sender.clone();
+            let items = &items;
+            let next_index = &next_index;
+            let f = &f;
+            scope.spawn(move || loop {
+                let index = next_index.fetch_add(1, Ordering::SeqCst);
+                match items.get(index) {
+                    Some(item) => {
+                        if sender.send((index, f(item))).is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending: BTreeMap<usize, R> = BTreeMap::new();
+        let mut next_to_consume = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&next_to_consume) {
+                consume(result);
+                next_to_consume += 1;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::for_each_in_order;
+
+    #[test]
+    fn results_are_consumed_in_order() {
+        for jobs in &[1, 2, 4, 20] {
+            let mut results = Vec::new();
+            for_each_in_order(
+                (0..10).collect(),
+                *jobs,
+                |i: &u64| {
+                    std::thread::sleep(std::time::Duration::from_millis((10 - i) * 3));
+                    i * 2
+                },
+                |r| results.push(r),
+            );
+            assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<u64>>());
+        }
+    }
+}
@@ -0,0 +1,220 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use gws::commands::branch::CreateBranch;
+use gws::commands::common::filter::ProjectFilter;
+use gws::commands::common::RepositoryCommand;
+use gws::commands::status::Status;
+use gws::config::data::Workspace;
+use gws::data::status::BranchCreation;
+use gws::data::status::BranchCreationOutcome;
+
+use util::in_example_workspace;
+use util::Error;
+
+fn create_branch(name: &str, projects: &[&str]) -> CreateBranch {
+    CreateBranch {
+        status_command: Status {
+            only_changes: false,
+            projects: ProjectFilter::from(
+                projects
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<HashSet<String>>(),
+            ),
+        },
+        name: name.to_string(),
+        from_default: false,
+        upstream_remote: None,
+    }
+}
+
+fn creations(
+    command: &CreateBranch,
+    working_dir: &Path,
+    workspace: &Workspace,
+) -> Vec<(String, BranchCreation)> {
+    command
+        .make_report(working_dir, workspace)
+        .into_iter()
+        .map(|(project, report)| (project.path.clone(), report.unwrap().created_branch))
+        .collect()
+}
+
+fn branch_target(
+    working_dir: &Path,
+    project: &str,
+    name: &str,
+    branch_type: git2::BranchType,
+) -> Result<git2::Oid, Error> {
+    let repo = git2::Repository::open(working_dir.join(project))?;
+    let target = repo
+        .find_branch(name, branch_type)?
+        .get()
+        .peel_to_commit()?
+        .id();
+    Ok(target)
+}
+
+#[test]
+fn branch_is_created_from_head() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let command = create_branch("topic", &["clean", "new_commit/local"]);
+
+        assert_eq!(
+            creations(&command, working_dir, &workspace),
+            vec![
+                (
+                    "clean".to_string(),
+                    BranchCreation {
+                        branch: "topic".to_string(),
+                        start_point: "master".to_string(),
+                        upstream_name: None,
+                        missing_upstream: None,
+                        outcome: BranchCreationOutcome::Created,
+                    }
+                ),
+                (
+                    "new_commit/local".to_string(),
+                    BranchCreation {
+                        branch: "topic".to_string(),
+                        start_point: "master".to_string(),
+                        upstream_name: None,
+                        missing_upstream: None,
+                        outcome: BranchCreationOutcome::Created,
+                    }
+                ),
+            ]
+        );
+
+        for project in &["clean", "new_commit/local"] {
+            assert_eq!(
+                branch_target(working_dir, project, "topic", git2::BranchType::Local)?,
+                branch_target(working_dir, project, "master", git2::BranchType::Local)?
+            );
+            let repo = git2::Repository::open(working_dir.join(project))?;
+            assert_eq!(repo.head()?.shorthand(), Some("master"));
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn branch_can_start_from_default_branch_and_report_missing_upstream() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let command = CreateBranch {
+            from_default: true,
+            upstream_remote: Some("origin".to_string()),
+            ..create_branch("topic", &["new_commit/local"])
+        };
+
+        assert_eq!(
+            creations(&command, working_dir, &workspace),
+            vec![(
+                "new_commit/local".to_string(),
+                BranchCreation {
+                    branch: "topic".to_string(),
+                    start_point: "origin/master".to_string(),
+                    upstream_name: None,
+                    missing_upstream: Some("origin/topic".to_string()),
+                    outcome: BranchCreationOutcome::Created,
+                }
+            )]
+        );
+
+        assert_eq!(
+            branch_target(
+                working_dir,
+                "new_commit/local",
+                "topic",
+                git2::BranchType::Local
+            )?,
+            branch_target(
+                working_dir,
+                "new_commit/local",
+                "origin/master",
+                git2::BranchType::Remote
+            )?
+        );
+
+        let repo = git2::Repository::open(working_dir.join("new_commit/local"))?;
+        let config = repo.config()?;
+        assert!(config.get_string("branch.topic.remote").is_err());
+        assert!(config.get_string("branch.topic.merge").is_err());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn branch_tracks_existing_remote_branch() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        {
+            let repo = git2::Repository::open(working_dir.join("new_commit/local"))?;
+            let target = repo.head()?.peel_to_commit()?.id();
+            repo.reference("refs/remotes/origin/topic", target, false, "test")?;
+        }
+        let command = CreateBranch {
+            upstream_remote: Some("origin".to_string()),
+            ..create_branch("topic", &["new_commit/local"])
+        };
+
+        assert_eq!(
+            creations(&command, working_dir, &workspace),
+            vec![(
+                "new_commit/local".to_string(),
+                BranchCreation {
+                    branch: "topic".to_string(),
+                    start_point: "master".to_string(),
+                    upstream_name: Some("origin/topic".to_string()),
+                    missing_upstream: None,
+                    outcome: BranchCreationOutcome::Created,
+                }
+            )]
+        );
+
+        let repo = git2::Repository::open(working_dir.join("new_commit/local"))?;
+        let config = repo.config()?;
+        assert_eq!(config.get_string("branch.topic.remote")?, "origin");
+        assert_eq!(config.get_string("branch.topic.merge")?, "refs/heads/topic");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn branch_reports_existing_branches_and_failures() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let existing = create_branch("feature", &["clean"]);
+        let outcomes: Vec<BranchCreationOutcome> = creations(&existing, working_dir, &workspace)
+            .into_iter()
+            .map(|(_, creation)| creation.outcome)
+            .collect();
+        assert_eq!(outcomes, vec![BranchCreationOutcome::AlreadyExists]);
+
+        let bad_remote = CreateBranch {
+            upstream_remote: Some("nope".to_string()),
+            ..create_branch("topic", &["clean"])
+        };
+        let outcomes: Vec<BranchCreationOutcome> = creations(&bad_remote, working_dir, &workspace)
+            .into_iter()
+            .map(|(_, creation)| creation.outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![BranchCreationOutcome::Failed(
+                "Remote nope not found".to_string()
+            )]
+        );
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        assert!(repo.find_branch("topic", git2::BranchType::Local).is_err());
+
+        Ok(())
+    })
+}
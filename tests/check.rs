@@ -0,0 +1,117 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::BTreeMap;
+
+use gws::color::palette::Palette;
+use gws::commands::check::Check;
+use gws::commands::check::RepositoryPresence;
+use gws::commands::common::exit_codes;
+use gws::commands::common::DirectoryCommand;
+use gws::config::data::Workspace;
+use gws::config::parse::ignore;
+
+use util::in_example_workspace;
+use util::Error;
+
+#[test]
+fn check_classifies_repos() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        git2::Repository::init(working_dir.join("unlisted"))?;
+        git2::Repository::init(working_dir.join("new_commit").join("unlisted"))?;
+
+        let report = Check {}
+            .make_report(working_dir, &workspace)
+            .expect("Check command failed");
+
+        let expected: BTreeMap<String, RepositoryPresence> = vec![
+            ("changes/changed_files", RepositoryPresence::Known),
+            ("changes/new_files", RepositoryPresence::Known),
+            ("clean", RepositoryPresence::Known),
+            ("missing_repository", RepositoryPresence::Missing),
+            ("missing_repository_2", RepositoryPresence::Missing),
+            ("new_commit/diverged", RepositoryPresence::Known),
+            ("new_commit/local", RepositoryPresence::Known),
+            ("new_commit/remote", RepositoryPresence::Known),
+            ("new_commit/unfetched_remote", RepositoryPresence::Known),
+            ("new_commit/unlisted", RepositoryPresence::Unknown),
+            ("no_upstream", RepositoryPresence::Known),
+            ("unlisted", RepositoryPresence::Unknown),
+        ]
+        .into_iter()
+        .map(|(path, presence)| (path.to_string(), presence))
+        .collect();
+
+        assert_eq!(report, expected);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn check_fails_if_workspace_is_inconsistent() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let exit_code = Check {}
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Check command failed");
+        assert_eq!(exit_code, exit_codes::INCONSISTENT_WORKSPACE);
+        Ok(())
+    })
+}
+
+#[test]
+fn check_succeeds_if_workspace_is_consistent() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let workspace = Workspace::from(
+            workspace
+                .projects
+                .into_iter()
+                .filter(|project| working_dir.join(&project.path).exists()),
+        );
+
+        let exit_code = Check {}
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Check command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+        Ok(())
+    })
+}
+
+#[test]
+fn check_reports_ignored_repos() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        git2::Repository::init(working_dir.join("unlisted"))?;
+        let workspace = workspace.ignoring(ignore::parse("^(clean|unlisted)$").unwrap());
+
+        let report = Check {}
+            .make_report(working_dir, &workspace)
+            .expect("Check command failed");
+
+        assert_eq!(report.get("clean"), Some(&RepositoryPresence::Ignored));
+        assert_eq!(report.get("unlisted"), Some(&RepositoryPresence::Ignored));
+        assert_eq!(report.get("no_upstream"), Some(&RepositoryPresence::Known));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn check_reports_listed_paths_that_are_not_repos() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        std::fs::create_dir(working_dir.join("missing_repository"))?;
+
+        let report = Check {}
+            .make_report(working_dir, &workspace)
+            .expect("Check command failed");
+
+        assert_eq!(
+            report.get("missing_repository"),
+            Some(&RepositoryPresence::NotARepository)
+        );
+        assert_eq!(report.get("clean"), Some(&RepositoryPresence::Known));
+
+        Ok(())
+    })
+}
@@ -2,6 +2,7 @@ mod util;
 
 use assert_cmd::assert::OutputAssertExt;
 use assert_cmd::cargo::CommandCargoExt;
+use std::fs::write;
 use std::process::Command;
 use util::in_example_workspace;
 
@@ -26,3 +27,370 @@ fn status_does_not_find_workspace_in_unrelated_dir() -> Result<(), util::Error>
     cmd.assert().failure();
     Ok(())
 }
+
+#[test]
+fn ignored_projects_are_not_cloned() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        write(
+            workspace_dir.join(".ignore.gws"),
+            "# Ignore only the first one\n^missing_repository$\n",
+        )?;
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("update");
+        cmd.assert().success();
+
+        assert!(!workspace_dir.join("missing_repository").exists());
+        assert!(workspace_dir.join("missing_repository_2").exists());
+        Ok(())
+    })
+}
+
+#[test]
+fn invalid_ignore_file_is_an_error() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        write(workspace_dir.join(".ignore.gws"), "^missing_(\n")?;
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.assert().failure();
+        Ok(())
+    })
+}
+
+#[test]
+fn projects_file_syntax_error_is_reported_with_line() -> Result<(), util::Error> {
+    let workspace_dir = tempfile::tempdir()?;
+    write(
+        workspace_dir.path().join(".projects.toml"),
+        "[projects.foo]\nremotes = { origin = \"https://example.org/foo.git\" \n",
+    )?;
+    let mut cmd = Command::cargo_bin("gws")?;
+    cmd.arg("-C").arg(workspace_dir.path().to_str().unwrap());
+    cmd.arg("--no-config");
+    let output = cmd.assert().code(3).get_output().stderr.clone();
+
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(stderr.contains(".projects.toml"), "stderr: {}", stderr);
+    assert!(stderr.contains("line 2"), "stderr: {}", stderr);
+    Ok(())
+}
+
+#[test]
+fn status_json_has_only_status_fields() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("status").arg("--format").arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        for project in report["projects"].as_array().unwrap() {
+            assert_eq!(
+                project
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<&str>>(),
+                vec!["branches", "error", "head", "path", "remotes"]
+            );
+        }
+        Ok(())
+    })
+}
+
+#[test]
+fn format_is_accepted_without_subcommand() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("--format").arg("json");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["projects"].as_array().unwrap().len(), 10);
+        Ok(())
+    })
+}
+
+#[test]
+fn status_prints_ndjson() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, workspace| {
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("status").arg("--format").arg("ndjson");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let projects: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(
+            projects
+                .iter()
+                .map(|p| p["path"].as_str().unwrap())
+                .collect::<Vec<&str>>(),
+            workspace
+                .projects
+                .iter()
+                .map(|p| p.path.as_str())
+                .collect::<Vec<&str>>(),
+        );
+
+        let changed_files = projects
+            .iter()
+            .find(|p| p["path"] == "changes/changed_files")
+            .unwrap();
+        assert_eq!(changed_files["branches"][0]["name"], "master");
+        assert_eq!(changed_files["branches"][0]["dirty"], "unstaged_changes");
+
+        let missing = projects
+            .iter()
+            .find(|p| p["path"] == "missing_repository")
+            .unwrap();
+        assert_eq!(missing["error"]["kind"], "repository_missing");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn toml_projects_file_takes_precedence() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("convert");
+        cmd.assert().success();
+
+        write(
+            workspace_dir.join(".projects.toml"),
+            "[projects.clean]\nremotes = [{ name = \"origin\", url = \"https://example.org/clean.git\" }]\n",
+        )?;
+
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("status").arg("--format").arg("ndjson");
+        let output = cmd.assert().success().get_output().stdout.clone();
+
+        let paths: Vec<String> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["path"].to_string()
+            })
+            .collect();
+        assert_eq!(paths, vec!["\"clean\"".to_string()]);
+        Ok(())
+    })
+}
+
+fn status_paths(
+    workspace_dir: &std::path::Path,
+    args: &[&str],
+) -> Result<Vec<String>, util::Error> {
+    let mut cmd = Command::cargo_bin("gws")?;
+    cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+    cmd.arg("--no-config");
+    cmd.arg("status").arg("--format").arg("ndjson");
+    cmd.args(args);
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    Ok(String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            serde_json::from_str::<serde_json::Value>(line).unwrap()["path"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect())
+}
+
+#[test]
+fn status_filters_projects_by_group() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        write(
+            workspace_dir.join(".projects.toml"),
+            r#"
+[projects.clean]
+groups = ["work"]
+remotes = [{ name = "origin", url = "https://example.org/clean.git" }]
+
+[projects.no_upstream]
+groups = ["oss"]
+remotes = [{ name = "origin", url = "https://example.org/no_upstream.git" }]
+
+[projects."new_commit/local"]
+groups = ["oss", "work"]
+remotes = [{ name = "origin", url = "https://example.org/local.git" }]
+
+[projects."new_commit/remote"]
+remotes = [{ name = "origin", url = "https://example.org/remote.git" }]
+"#,
+        )?;
+
+        assert_eq!(
+            status_paths(workspace_dir, &["--group", "work"])?,
+            vec!["clean", "new_commit/local"]
+        );
+        assert_eq!(
+            status_paths(workspace_dir, &["--exclude-group", "oss"])?,
+            vec!["clean", "new_commit/remote"]
+        );
+        assert_eq!(
+            status_paths(
+                workspace_dir,
+                &["--group", "work", "--exclude-group", "oss"]
+            )?,
+            vec!["clean"]
+        );
+        assert_eq!(
+            status_paths(workspace_dir, &["-g", "oss", "-g", "work", "clean"])?,
+            vec!["clean"]
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn path_args_match_globs_and_directories() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        assert_eq!(
+            status_paths(workspace_dir, &["new_commit/*ote"])?,
+            vec!["new_commit/remote", "new_commit/unfetched_remote"]
+        );
+        assert_eq!(
+            status_paths(workspace_dir, &["changes/", "clean"])?,
+            vec!["changes/changed_files", "changes/new_files", "clean"]
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn path_args_are_relative_to_working_dir() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let subdir = workspace_dir.join("new_commit");
+        assert_eq!(
+            status_paths(&subdir, &["."])?,
+            vec![
+                "new_commit/diverged",
+                "new_commit/local",
+                "new_commit/remote",
+                "new_commit/unfetched_remote",
+            ]
+        );
+        assert_eq!(
+            status_paths(&subdir, &["local", "../clean"])?,
+            vec!["clean", "new_commit/local"]
+        );
+        Ok(())
+    })
+}
+
+#[test]
+fn path_arg_matching_no_project_is_an_error() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C").arg(workspace_dir.to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("fetch").arg("clean").arg("nonexistent/*");
+        let stderr = cmd
+            .assert()
+            .code(gws::commands::common::exit_codes::USER_ERROR)
+            .get_output()
+            .stderr
+            .clone();
+        assert!(String::from_utf8(stderr).unwrap().contains("nonexistent/*"));
+        Ok(())
+    })
+}
+
+#[test]
+fn here_selects_enclosing_project() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let project_dir = workspace_dir.join("new_commit").join("local");
+        assert_eq!(
+            status_paths(&project_dir, &["--here"])?,
+            vec!["new_commit/local"]
+        );
+        assert_eq!(status_paths(&project_dir, &[])?.len(), 10);
+
+        let mut cmd = Command::cargo_bin("gws")?;
+        cmd.arg("-C")
+            .arg(workspace_dir.join("new_commit").to_str().unwrap());
+        cmd.arg("--no-config");
+        cmd.arg("status").arg("--here");
+        cmd.assert()
+            .code(gws::commands::common::exit_codes::USER_ERROR);
+        Ok(())
+    })
+}
+
+#[test]
+fn here_can_be_enabled_in_config() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let config_home = tempfile::tempdir()?;
+        std::fs::create_dir_all(config_home.path().join("gws"))?;
+        write(
+            config_home.path().join("gws").join("config.toml"),
+            "here = true\n",
+        )?;
+
+        let run = |args: &[&str]| -> Result<usize, util::Error> {
+            let mut cmd = Command::cargo_bin("gws")?;
+            cmd.env("XDG_CONFIG_HOME", config_home.path());
+            cmd.arg("-C")
+                .arg(workspace_dir.join("clean").to_str().unwrap());
+            cmd.arg("status").arg("--format").arg("ndjson");
+            cmd.args(args);
+            let output = cmd.assert().success().get_output().stdout.clone();
+            Ok(String::from_utf8(output).unwrap().lines().count())
+        };
+
+        assert_eq!(run(&[])?, 1);
+        assert_eq!(run(&["--no-here"])?, 10);
+        assert_eq!(run(&["../no_upstream", "../changes"])?, 3);
+        Ok(())
+    })
+}
+
+#[test]
+fn here_setting_includes_all_projects_outside_any_project() -> Result<(), util::Error> {
+    in_example_workspace(|workspace_dir, _| {
+        let config_home = tempfile::tempdir()?;
+        std::fs::create_dir_all(config_home.path().join("gws"))?;
+        write(
+            config_home.path().join("gws").join("config.toml"),
+            "here = true\n",
+        )?;
+
+        for dir in &[
+            workspace_dir.to_path_buf(),
+            workspace_dir.join("new_commit"),
+        ] {
+            let mut cmd = Command::cargo_bin("gws")?;
+            cmd.env("XDG_CONFIG_HOME", config_home.path());
+            cmd.arg("-C").arg(dir.to_str().unwrap());
+            cmd.assert().success();
+
+            let mut cmd = Command::cargo_bin("gws")?;
+            cmd.env("XDG_CONFIG_HOME", config_home.path());
+            cmd.arg("-C").arg(dir.to_str().unwrap());
+            cmd.arg("status").arg("--format").arg("ndjson");
+            let output = cmd.assert().success().get_output().stdout.clone();
+            assert_eq!(String::from_utf8(output).unwrap().lines().count(), 10);
+        }
+        Ok(())
+    })
+}
@@ -11,6 +11,8 @@ use git2::Repository;
 
 use gws::color::palette::Palette;
 use gws::commands::clone::Clone;
+use gws::commands::common::credentials::Credentials;
+use gws::commands::common::filter::ProjectFilter;
 use gws::commands::common::DirectoryCommand;
 use gws::config::data::Workspace;
 
@@ -31,7 +33,9 @@ where
 fn clone_creates_repo() -> Result<(), Error> {
     in_example_workspace(|working_dir, workspace: Workspace| {
         let command: Clone = Clone {
-            projects: hash_set(vec!["missing_repository".to_string()]),
+            projects: ProjectFilter::from(hash_set(vec!["missing_repository".to_string()])),
+            jobs: 1,
+            credentials: Credentials::default(),
         };
 
         assert_eq!(false, working_dir.join("missing_repository").exists());
@@ -49,10 +53,12 @@ fn clone_creates_repo() -> Result<(), Error> {
 fn clone_supports_multiple_arguments() -> Result<(), Error> {
     in_example_workspace(|working_dir, workspace: Workspace| {
         let command: Clone = Clone {
-            projects: hash_set(vec![
+            projects: ProjectFilter::from(hash_set(vec![
                 "missing_repository".to_string(),
                 "missing_repository_2".to_string(),
-            ]),
+            ])),
+            jobs: 1,
+            credentials: Credentials::default(),
         };
 
         assert_eq!(false, working_dir.join("missing_repository").exists());
@@ -71,7 +77,9 @@ fn clone_supports_multiple_arguments() -> Result<(), Error> {
 fn clone_creates_extra_remotes() -> Result<(), Error> {
     in_example_workspace(|working_dir, workspace: Workspace| {
         let command: Clone = Clone {
-            projects: hash_set(vec!["missing_repository".to_string()]),
+            projects: ProjectFilter::from(hash_set(vec!["missing_repository".to_string()])),
+            jobs: 1,
+            credentials: Credentials::default(),
         };
 
         let repo_path: String = workspace
@@ -107,7 +115,9 @@ fn clone_works_with_public_https() -> Result<(), Error> {
 
     in_workspace_with_projects_file(projects_contents, |working_dir, workspace: Workspace| {
         let command: Clone = Clone {
-            projects: hash_set(vec!["gws2".to_string()]),
+            projects: ProjectFilter::from(hash_set(vec!["gws2".to_string()])),
+            jobs: 1,
+            credentials: Credentials::default(),
         };
 
         let repo_path: String = workspace
@@ -145,7 +155,9 @@ fn clone_works_with_ssh() -> Result<(), Error> {
         projects_contents,
         with_bundled_ssh_key_in_agent(|working_dir, workspace: Workspace| {
             let command: Clone = Clone {
-                projects: hash_set(vec!["gws2".to_string()]),
+                projects: ProjectFilter::from(hash_set(vec!["gws2".to_string()])),
+                jobs: 1,
+                credentials: Credentials::default(),
             };
 
             let repo_path: String = workspace
@@ -175,3 +187,85 @@ fn clone_works_with_ssh() -> Result<(), Error> {
         })?,
     )
 }
+
+#[test]
+fn clone_supports_parallel_jobs() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let command: Clone = Clone {
+            projects: ProjectFilter::from(hash_set(vec![
+                "missing_repository".to_string(),
+                "missing_repository_2".to_string(),
+            ])),
+            jobs: 2,
+            credentials: Credentials::default(),
+        };
+
+        let exit_code = command
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Clone command failed");
+
+        assert_eq!(exit_code, gws::commands::common::exit_codes::OK);
+        for path in &["missing_repository", "missing_repository_2"] {
+            assert_eq!(
+                Repository::open(working_dir.join(path))
+                    .expect("Failed to open repo")
+                    .remotes()
+                    .expect("Failed to get remotes")
+                    .iter()
+                    .map(Option::unwrap)
+                    .collect::<HashSet<&str>>(),
+                hash_set(vec!["origin", "ahead"])
+            );
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn clone_stops_after_submodule_update_fails() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let origin_url = workspace
+            .projects
+            .iter()
+            .find(|project| project.path == "missing_repository")
+            .unwrap()
+            .main_remote
+            .url
+            .clone();
+        let origin = Repository::open(&origin_url)?;
+        {
+            let gitmodules = origin.blob(
+                format!(
+                    "[submodule \"sub\"]\n\tpath = sub\n\turl = {}\n",
+                    working_dir.join("nonexistent").display()
+                )
+                .as_bytes(),
+            )?;
+            let head = origin.head()?.peel_to_commit()?;
+            let mut tree = origin.treebuilder(Some(&head.tree()?))?;
+            tree.insert(".gitmodules", gitmodules, 0o100644)?;
+            tree.insert("sub", head.id(), 0o160000)?;
+            let tree = origin.find_tree(tree.write()?)?;
+            let sig = origin.signature()?;
+            origin.commit(Some("HEAD"), &sig, &sig, "Add submodule", &tree, &[&head])?;
+        }
+
+        let command: Clone = Clone {
+            projects: ProjectFilter::from(hash_set(vec![
+                "missing_repository".to_string(),
+                "missing_repository_2".to_string(),
+            ])),
+            jobs: 1,
+            credentials: Credentials::default(),
+        };
+
+        assert!(command
+            .run(working_dir, &workspace, &Palette::default())
+            .is_err());
+        assert!(working_dir.join("missing_repository").exists());
+        assert!(!working_dir.join("missing_repository_2").exists());
+
+        Ok(())
+    })
+}
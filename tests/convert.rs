@@ -0,0 +1,47 @@
+extern crate gws;
+
+mod util;
+
+use std::fs::write;
+
+use gws::color::palette::Palette;
+use gws::commands::common::exit_codes;
+use gws::commands::common::DirectoryCommand;
+use gws::commands::convert::Convert;
+use gws::config::read::read_workspace_file;
+
+use util::in_example_workspace;
+use util::Error;
+
+#[test]
+fn convert_preserves_projects() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let exit_code = Convert {}
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Convert command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+
+        assert_eq!(
+            read_workspace_file(working_dir.join(".projects.toml")).unwrap(),
+            read_workspace_file(working_dir.join(".projects.gws")).unwrap()
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn convert_refuses_to_overwrite_toml_file() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let toml_file_path = working_dir.join(".projects.toml");
+        write(&toml_file_path, "")?;
+
+        let exit_code = Convert {}
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Convert command failed");
+        assert_eq!(exit_code, exit_codes::USER_ERROR);
+        assert_eq!(std::fs::read_to_string(&toml_file_path)?, "");
+
+        Ok(())
+    })
+}
@@ -0,0 +1,113 @@
+extern crate gws;
+
+mod util;
+
+use std::collections::HashSet;
+
+use gws::commands::common::exit_codes;
+use gws::commands::common::filter::ProjectFilter;
+use gws::commands::exec::report_exit_code;
+use gws::commands::exec::Exec;
+use gws::commands::exec::ExecOutcome;
+
+use util::in_example_workspace;
+use util::Error;
+
+fn exec(projects: &[&str], fail_fast: bool, script: &str) -> Exec {
+    Exec {
+        projects: ProjectFilter::from(
+            projects
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<HashSet<String>>(),
+        ),
+        jobs: 4,
+        fail_fast,
+        command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+    }
+}
+
+#[test]
+fn exec_runs_command_in_each_project_directory() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let command = exec(&[], false, "pwd");
+        let report = command.make_report_and_maybe_print(working_dir, &workspace, None);
+
+        assert_eq!(report.len(), workspace.projects.len());
+        for (project, outcome) in &report {
+            match outcome {
+                ExecOutcome::Missing => assert!(project.path.starts_with("missing_repository")),
+                ExecOutcome::Finished(output) => {
+                    assert!(output.status.success());
+                    assert_eq!(
+                        String::from_utf8_lossy(&output.stdout).trim(),
+                        working_dir.join(&project.path).to_string_lossy()
+                    );
+                }
+                other => panic!("Unexpected outcome for {}: {:?}", project.path, other),
+            }
+        }
+        assert_eq!(report_exit_code(&report), exit_codes::OK);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn exec_fails_if_command_fails_in_any_project() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let command = exec(
+            &["clean", "no_upstream"],
+            false,
+            "test \"$(basename \"$PWD\")\" = clean",
+        );
+        let report = command.make_report_and_maybe_print(working_dir, &workspace, None);
+
+        let successes: Vec<(&str, bool)> = report
+            .iter()
+            .map(|(project, outcome)| match outcome {
+                ExecOutcome::Finished(output) => (project.path.as_str(), output.status.success()),
+                other => panic!("Unexpected outcome for {}: {:?}", project.path, other),
+            })
+            .collect();
+        assert_eq!(successes, vec![("clean", true), ("no_upstream", false)]);
+        assert_eq!(report_exit_code(&report), exit_codes::UNKNOWN_ERROR);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn exec_fail_fast_skips_remaining_projects() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let command = Exec {
+            jobs: 1,
+            ..exec(&["changes", "clean"], true, "false")
+        };
+        let report = command.make_report_and_maybe_print(working_dir, &workspace, None);
+
+        assert_eq!(report.len(), 3);
+        assert!(matches!(report[0].1, ExecOutcome::Finished(_)));
+        assert!(matches!(report[1].1, ExecOutcome::Skipped));
+        assert!(matches!(report[2].1, ExecOutcome::Skipped));
+        assert_eq!(report_exit_code(&report), exit_codes::UNKNOWN_ERROR);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn exec_reports_commands_that_cannot_be_started() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let command = Exec {
+            command: vec!["gws-test-no-such-command".to_string()],
+            ..exec(&["clean"], false, "")
+        };
+        let report = command.make_report_and_maybe_print(working_dir, &workspace, None);
+
+        assert!(matches!(report[0].1, ExecOutcome::Error(_)));
+        assert_eq!(report_exit_code(&report), exit_codes::USER_ERROR);
+
+        Ok(())
+    })
+}
@@ -12,10 +12,15 @@ use git2::Commit;
 use git2::Repository;
 
 use gws::color::palette::Palette;
+use gws::commands::common::credentials::Credentials;
+use gws::commands::common::exit_codes;
+use gws::commands::common::filter::ProjectFilter;
 use gws::commands::common::RepositoryCommand;
 use gws::commands::fetch::Fetch;
 use gws::commands::status::Status;
 use gws::config::data::Workspace;
+use gws::data::status::FetchError;
+use gws::data::status::FetchErrorKind;
 
 use util::in_example_workspace;
 use util::Error;
@@ -43,8 +48,13 @@ fn fetch_gets_refs_from_named_remotes() -> Result<(), Error> {
         let command: Fetch = Fetch {
             status_command: Status {
                 only_changes: false,
-                projects: HashSet::new(),
+                projects: ProjectFilter::default(),
             },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
         };
 
         let repo: Repository = Repository::open(working_dir.join(project_path))?;
@@ -79,15 +89,20 @@ fn fetch_reports_updates() -> Result<(), Error> {
         let command: Fetch = Fetch {
             status_command: Status {
                 only_changes: false,
-                projects: HashSet::new(),
+                projects: ProjectFilter::default(),
             },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
         };
 
         let status_report_1 = command.make_report(working_dir, &workspace);
 
         for (project, project_status) in status_report_1 {
             if project.path == project_path {
-                let project_status = project_status.unwrap();
+                let project_status = project_status.unwrap().status;
                 for branch_status in project_status {
                     if branch_status.name == "master" || branch_status.name == "master2" {
                         assert_eq!(branch_status.upstream_fetched, true);
@@ -102,7 +117,7 @@ fn fetch_reports_updates() -> Result<(), Error> {
 
         for (project, project_status) in status_report_2 {
             if project.path == project_path {
-                let project_status = project_status.unwrap();
+                let project_status = project_status.unwrap().status;
                 for branch_status in project_status {
                     assert_eq!(branch_status.upstream_fetched, false);
                 }
@@ -121,15 +136,20 @@ fn fetch_fetches_all_projects_if_none_are_named() -> Result<(), Error> {
         let command: Fetch = Fetch {
             status_command: Status {
                 only_changes: false,
-                projects: HashSet::new(),
+                projects: ProjectFilter::default(),
             },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
         };
 
         let status_report = command.make_report(working_dir, &workspace);
 
         for (project, project_status) in status_report {
             if project.path == project_path {
-                let project_status = project_status.unwrap();
+                let project_status = project_status.unwrap().status;
                 for branch_status in project_status {
                     if branch_status.name == "master" || branch_status.name == "master2" {
                         assert_eq!(branch_status.upstream_fetched, true);
@@ -157,15 +177,20 @@ fn fetch_fetches_only_named_projects_if_any_are_named() -> Result<(), Error> {
         let command: Fetch = Fetch {
             status_command: Status {
                 only_changes: false,
-                projects,
+                projects: ProjectFilter::from(projects),
             },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
         };
 
         let status_report = command.make_report(working_dir, &workspace);
 
         for (project, project_status) in status_report {
             if project.path == project_path {
-                let project_status = project_status.unwrap();
+                let project_status = project_status.unwrap().status;
                 for branch_status in project_status {
                     if branch_status.name == "master" || branch_status.name == "master2" {
                         assert_eq!(branch_status.upstream_fetched, should_fetch);
@@ -197,3 +222,261 @@ fn fetch_fetches_only_named_projects_if_any_are_named() -> Result<(), Error> {
         )
     })
 }
+
+#[test]
+fn parallel_fetch_produces_same_report_as_sequential() -> Result<(), Error> {
+    fn make_command(jobs: usize) -> Fetch {
+        Fetch {
+            status_command: Status {
+                only_changes: false,
+                projects: ProjectFilter::default(),
+            },
+            jobs,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
+        }
+    }
+
+    let sequential_report = in_example_workspace(|working_dir, workspace: Workspace| {
+        Ok(make_command(1)
+            .make_report(working_dir, &workspace)
+            .into_iter()
+            .map(|(project, status)| (project.path.clone(), status))
+            .collect::<Vec<_>>())
+    })?;
+
+    let parallel_report = in_example_workspace(|working_dir, workspace: Workspace| {
+        Ok(make_command(4)
+            .make_report(working_dir, &workspace)
+            .into_iter()
+            .map(|(project, status)| (project.path.clone(), status))
+            .collect::<Vec<_>>())
+    })?;
+
+    assert_eq!(parallel_report, sequential_report);
+
+    Ok(())
+}
+
+#[test]
+fn fetch_reports_failed_remotes() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        Repository::open(working_dir.join("clean"))?
+            .remote_set_url("ahead", working_dir.join("nonexistent").to_str().unwrap())?;
+
+        let command: Fetch = Fetch {
+            status_command: Status {
+                only_changes: false,
+                projects: ProjectFilter::from(hash_set(vec![
+                    "clean".to_string(),
+                    "new_commit/diverged".to_string(),
+                ])),
+            },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
+        };
+
+        let fetch_errors: Vec<(String, Vec<FetchError>)> = command
+            .make_report(working_dir, &workspace)
+            .into_iter()
+            .map(|(project, status)| (project.path.clone(), status.unwrap().fetch_errors))
+            .collect();
+
+        assert_eq!(fetch_errors[0].0, "clean");
+        assert_eq!(fetch_errors[0].1.len(), 1);
+        assert_eq!(fetch_errors[0].1[0].remote, "ahead");
+
+        assert_eq!(fetch_errors[1].0, "new_commit/diverged");
+        assert_eq!(
+            fetch_errors[1].1,
+            vec![FetchError {
+                remote: "ahead".to_string(),
+                kind: FetchErrorKind::MissingRemote,
+                message: "Remote ahead not found in repository".to_string(),
+            }]
+        );
+
+        let exit_code = command
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Fetch command failed");
+        assert_eq!(exit_code, exit_codes::INTERNAL_ERROR);
+
+        Ok(())
+    })
+}
+
+fn fetch_clean(prune: Option<bool>, tags: Option<bool>, remote: Option<&str>) -> Fetch {
+    Fetch {
+        status_command: Status {
+            only_changes: false,
+            projects: ProjectFilter::from(hash_set(vec!["clean".to_string()])),
+        },
+        jobs: 1,
+        credentials: Credentials::default(),
+        prune,
+        tags,
+        remote: remote.map(str::to_string),
+    }
+}
+
+/// Fetch the `clean` project, and return the remote-tracking branches it
+/// reports as pruned.
+fn pruned_refs(command: &Fetch, working_dir: &Path, workspace: &Workspace) -> Vec<String> {
+    command
+        .make_report(working_dir, workspace)
+        .into_values()
+        .flat_map(|report| report.unwrap().pruned_refs)
+        .collect()
+}
+
+/// Create `origin/stale` in the `clean` project, a remote-tracking branch
+/// with no branch on `origin`.
+fn make_stale_ref(working_dir: &Path) -> Result<Repository, Error> {
+    let repo = Repository::open(working_dir.join("clean"))?;
+    let head = repo.head()?.peel_to_commit()?.id();
+    repo.reference("refs/remotes/origin/stale", head, false, "test")?;
+    Ok(repo)
+}
+
+#[test]
+fn fetch_prune_removes_stale_remote_tracking_branches() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let repo = make_stale_ref(working_dir)?;
+
+        assert_eq!(
+            pruned_refs(&fetch_clean(None, None, None), working_dir, &workspace),
+            Vec::<String>::new()
+        );
+        assert!(repo.find_branch("origin/stale", BranchType::Remote).is_ok());
+
+        assert_eq!(
+            pruned_refs(
+                &fetch_clean(Some(true), None, None),
+                working_dir,
+                &workspace
+            ),
+            vec!["origin/stale".to_string()]
+        );
+        assert!(repo
+            .find_branch("origin/stale", BranchType::Remote)
+            .is_err());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn fetch_honors_prune_config() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let repo = make_stale_ref(working_dir)?;
+        let mut config = repo.config()?;
+
+        config.set_bool("fetch.prune", true)?;
+        config.set_bool("remote.origin.prune", false)?;
+        assert_eq!(
+            pruned_refs(&fetch_clean(None, None, None), working_dir, &workspace),
+            Vec::<String>::new()
+        );
+
+        config.remove("remote.origin.prune")?;
+        assert_eq!(
+            pruned_refs(
+                &fetch_clean(Some(false), None, None),
+                working_dir,
+                &workspace
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            pruned_refs(&fetch_clean(None, None, None), working_dir, &workspace),
+            vec!["origin/stale".to_string()]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn fetch_tags_options_control_tag_download() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let repo = Repository::open(working_dir.join("clean"))?;
+        let origin = Repository::open(repo.find_remote("origin")?.url().unwrap())?;
+        let origin_head = origin.head()?.peel_to_commit()?;
+        origin.tag_lightweight("v1.0", origin_head.as_object(), false)?;
+
+        fetch_clean(None, Some(false), Some("origin")).make_report(working_dir, &workspace);
+        assert!(repo.find_reference("refs/tags/v1.0").is_err());
+
+        fetch_clean(None, Some(true), Some("origin")).make_report(working_dir, &workspace);
+        assert!(repo.find_reference("refs/tags/v1.0").is_ok());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn fetch_remote_only_fetches_named_remote() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        Repository::open(working_dir.join("clean"))?
+            .remote_set_url("ahead", working_dir.join("nonexistent").to_str().unwrap())?;
+
+        let fetch_errors: Vec<FetchError> = fetch_clean(None, None, Some("origin"))
+            .make_report(working_dir, &workspace)
+            .into_values()
+            .flat_map(|status| status.unwrap().fetch_errors)
+            .collect();
+        assert_eq!(fetch_errors, vec![]);
+
+        let fetch_errors: Vec<FetchError> = fetch_clean(None, None, Some("ahead"))
+            .make_report(working_dir, &workspace)
+            .into_values()
+            .flat_map(|status| status.unwrap().fetch_errors)
+            .collect();
+        assert_eq!(fetch_errors.len(), 1);
+        assert_eq!(fetch_errors[0].remote, "ahead");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn fetch_reports_upstream_gone_after_pruning_it() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let repo = make_stale_ref(working_dir)?;
+        repo.find_branch("feature", BranchType::Local)?
+            .set_upstream(Some("origin/stale"))?;
+
+        let gone: Vec<(String, bool)> = fetch_clean(Some(true), None, None)
+            .make_report(working_dir, &workspace)
+            .into_values()
+            .flat_map(|report| report.unwrap().status.into_iter())
+            .filter(|branch| branch.name == "feature")
+            .map(|branch| (branch.name.clone(), branch.upstream_gone()))
+            .collect();
+        assert_eq!(gone, vec![("feature".to_string(), true)]);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn fetch_remote_missing_from_all_projects_is_an_error() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let exit_code = fetch_clean(None, None, Some("orign"))
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Fetch command failed");
+        assert_eq!(exit_code, exit_codes::USER_ERROR);
+
+        let exit_code = fetch_clean(None, None, Some("ahead"))
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Fetch command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+
+        Ok(())
+    })
+}
@@ -3,7 +3,6 @@ extern crate gws;
 
 mod util;
 
-use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::hash::Hash;
 use std::path::Path;
@@ -12,6 +11,8 @@ use git2::Commit;
 use git2::Repository;
 
 use gws::color::palette::Palette;
+use gws::commands::common::credentials::Credentials;
+use gws::commands::common::filter::ProjectFilter;
 use gws::commands::common::RepositoryCommand;
 use gws::commands::fetch::Fetch;
 use gws::commands::ff::FastForward;
@@ -19,17 +20,33 @@ use gws::commands::status::Status;
 use gws::config::data::Workspace;
 use gws::data::status::BranchStatus;
 use gws::data::status::DirtyState;
+use gws::data::status::FetchError;
+use gws::data::status::FetchErrorKind;
+use gws::data::status::Head;
 use gws::data::status::RepositoryStatus;
 
 use util::in_example_workspace;
 use util::Error;
 
-pub fn tree_set<I, T>(items: I) -> BTreeSet<T>
+/// The status of a repository with `master` checked out.
+pub fn repository_status<I>(branches: I) -> RepositoryStatus
 where
-    I: IntoIterator<Item = T>,
-    T: Ord,
+    I: IntoIterator<Item = BranchStatus>,
 {
-    items.into_iter().collect()
+    RepositoryStatus {
+        branches: branches.into_iter().collect(),
+        head: Head::Branch("master".to_string()),
+    }
+}
+
+/// `new_commit/diverged` is cloned from the `ahead` repository, so it has no
+/// `ahead` remote even though the projects file lists one.
+fn missing_ahead_remote() -> FetchError {
+    FetchError {
+        remote: "ahead".to_string(),
+        kind: FetchErrorKind::MissingRemote,
+        message: "Remote ahead not found in repository".to_string(),
+    }
 }
 
 pub fn hash_set<I, T>(items: I) -> HashSet<T>
@@ -60,9 +77,15 @@ fn ff_gets_refs_and_updates_heads() -> Result<(), util::Error> {
             fetch_command: Fetch {
                 status_command: Status {
                     only_changes: false,
-                    projects: HashSet::new(),
+                    projects: ProjectFilter::default(),
                 },
+                jobs: 1,
+                credentials: Credentials::default(),
+                prune: None,
+                tags: None,
+                remote: None,
             },
+            rebase: false,
         };
 
         let repo: Repository = Repository::open(working_dir.join(project_path))?;
@@ -126,16 +149,22 @@ fn run_test(
         fetch_command: Fetch {
             status_command: Status {
                 only_changes: false,
-                projects,
+                projects: ProjectFilter::from(projects),
             },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
         },
+        rebase: false,
     };
 
     let status_report = command.make_report(working_dir, &workspace);
 
     for (project, project_status) in status_report {
         if project.path == project_path {
-            let project_status = project_status.unwrap();
+            let project_status = project_status.unwrap().fetch.status;
             for branch_status in project_status {
                 if branch_status.name == "master" || branch_status.name == "master2" {
                     assert_eq!(branch_status.fast_forwarded, should_ff);
@@ -184,81 +213,123 @@ fn ff_produces_correct_data_structure() -> Result<(), Error> {
             fetch_command: Fetch {
                 status_command: Status {
                     only_changes: false,
-                    projects: HashSet::new(),
+                    projects: ProjectFilter::default(),
                 },
+                jobs: 1,
+                credentials: Credentials::default(),
+                prune: None,
+                tags: None,
+                remote: None,
             },
+            rebase: false,
         };
 
-        let status_report: Vec<Result<RepositoryStatus, gws::commands::error::Error>> = command
-            .make_report(working_dir, &workspace)
+        let report = command.make_report(working_dir, &workspace);
+        let fetch_errors: Vec<&FetchError> = report
+            .values()
+            .flat_map(|report| report.iter().flat_map(|report| &report.fetch.fetch_errors))
+            .collect();
+        assert_eq!(fetch_errors, vec![&missing_ahead_remote()]);
+
+        let status_report: Vec<Result<RepositoryStatus, gws::commands::error::Error>> = report
             .into_iter()
-            .map(|(_, status)| status)
+            .map(|(_, report)| report.map(|report| report.fetch.status))
             .collect();
 
         assert_eq!(
             status_report,
             vec![
                 // changes/changed_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
-                        dirty: DirtyState::UncommittedChanges,
+                        dirty: DirtyState::UnstagedChanges,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // changes/new_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::UntrackedFiles,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // clean
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "feature".to_string(),
                         upstream_name: None,
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: None,
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // missing_repository
@@ -266,96 +337,141 @@ fn ff_produces_correct_data_structure() -> Result<(), Error> {
                 // missing_repository_2
                 Err(gws::commands::error::Error::RepositoryMissing),
                 // new_commit/diverged
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: Some("origin/master".to_string()),
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: Some(false),
+                    ahead: 1,
+                    behind: 1,
                     upstream_fetched: false,
                     fast_forwarded: false,
-                },])),
+                    rebased: false,
+                }])),
                 // new_commit/local
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(false),
+                        ahead: 1,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/remote
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(false),
+                        ahead: 0,
+                        behind: 1,
                         upstream_fetched: false,
                         fast_forwarded: true,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(false), // It was not in sync before fast-forward
+                        ahead: 0,
+                        behind: 1,
                         upstream_fetched: false,
                         fast_forwarded: true,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "merginator".to_string(),
                         upstream_name: Some("ahead/merginator".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(false), // It was not in sync before fast-forward
+                        ahead: 0,
+                        behind: 4,
                         upstream_fetched: false,
                         fast_forwarded: true,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/unfetched_remote
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: true,
                         fast_forwarded: true,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true), // It was in sync before fetching
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: true,
                         fast_forwarded: true,
+                        rebased: false,
                     }
                 ])),
                 // no_upstream
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: None,
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: None,
+                    ahead: 0,
+                    behind: 0,
                     upstream_fetched: false,
                     fast_forwarded: false,
+                    rebased: false,
                 },])),
             ]
         );
@@ -364,73 +480,103 @@ fn ff_produces_correct_data_structure() -> Result<(), Error> {
             command
                 .make_report(working_dir, &workspace)
                 .into_iter()
-                .map(|(_, status)| status)
+                .map(|(_, report)| report.map(|report| report.fetch.status))
                 .collect();
 
         assert_eq!(
             second_status_report,
             vec![
                 // changes/changed_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
-                        dirty: DirtyState::UncommittedChanges,
+                        dirty: DirtyState::UnstagedChanges,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // changes/new_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::UntrackedFiles,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // clean
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "feature".to_string(),
                         upstream_name: None,
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: None,
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // missing_repository
@@ -438,96 +584,141 @@ fn ff_produces_correct_data_structure() -> Result<(), Error> {
                 // missing_repository_2
                 Err(gws::commands::error::Error::RepositoryMissing),
                 // new_commit/diverged
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: Some("origin/master".to_string()),
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: Some(false),
+                    ahead: 1,
+                    behind: 1,
                     upstream_fetched: false,
                     fast_forwarded: false,
-                },])),
+                    rebased: false,
+                }])),
                 // new_commit/local
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(false),
+                        ahead: 1,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/remote
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "merginator".to_string(),
                         upstream_name: Some("ahead/merginator".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/unfetched_remote
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // no_upstream
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: None,
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: None,
+                    ahead: 0,
+                    behind: 0,
                     upstream_fetched: false,
                     fast_forwarded: false,
+                    rebased: false,
                 },])),
             ]
         );
@@ -535,3 +726,145 @@ fn ff_produces_correct_data_structure() -> Result<(), Error> {
         Ok(())
     })
 }
+
+fn rebase_command(project_path: &str) -> FastForward {
+    FastForward {
+        fetch_command: Fetch {
+            status_command: Status {
+                only_changes: false,
+                projects: ProjectFilter::from(hash_set(vec![project_path.to_string()])),
+            },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: None,
+            tags: None,
+            remote: None,
+        },
+        rebase: true,
+    }
+}
+
+/// Write `content` to `file_name` and commit it onto `parent`, moving `HEAD`
+/// to the new commit.
+fn commit_file(
+    repo: &Repository,
+    file_name: &str,
+    content: &str,
+    parent: git2::Oid,
+) -> Result<git2::Oid, Error> {
+    std::fs::write(repo.workdir().unwrap().join(file_name), content)?;
+    let mut index = repo.index()?;
+    index.add_path(Path::new(file_name))?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    repo.head()?
+        .resolve()?
+        .set_target(parent, "Prepare test commit")?;
+    let sig = git2::Signature::now("Test", "test@example.org")?;
+    Ok(repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        file_name,
+        &tree,
+        &[&repo.find_commit(parent)?],
+    )?)
+}
+
+fn set_identity(repo: &Repository) -> Result<(), Error> {
+    let mut config = repo.config()?;
+    config.set_str("user.name", "Test")?;
+    config.set_str("user.email", "test@example.org")?;
+    Ok(())
+}
+
+#[test]
+fn ff_does_not_rebase_by_default() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let project_path = "new_commit/diverged";
+        let repo = Repository::open(working_dir.join(project_path))?;
+        set_identity(&repo)?;
+        let head_before = repo.head()?.peel_to_commit()?.id();
+
+        let command = FastForward {
+            rebase: false,
+            ..rebase_command(project_path)
+        };
+        let status_report = command.make_report(working_dir, &workspace);
+
+        let (_, status) = status_report.into_iter().next().unwrap();
+        let report = status.unwrap();
+        let status = report.fetch.status;
+        assert!(status.iter().all(|branch| !branch.rebased));
+        assert_eq!(report.ff_errors, vec![]);
+        assert_eq!(repo.head()?.peel_to_commit()?.id(), head_before);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn ff_rebase_rebases_diverged_branches() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let project_path = "new_commit/diverged";
+        let repo = Repository::open(working_dir.join(project_path))?;
+        set_identity(&repo)?;
+        let head_before = repo.head()?.peel_to_commit()?.id();
+        commit_file(&repo, "local.txt", "Local work", head_before)?;
+
+        let status_report = rebase_command(project_path).make_report(working_dir, &workspace);
+
+        let (_, status) = status_report.into_iter().next().unwrap();
+        let report = status.unwrap();
+        let status = report.fetch.status;
+        let master = status.iter().find(|b| b.name == "master").unwrap();
+        assert!(master.rebased);
+        assert!(!master.fast_forwarded);
+        assert_eq!(report.ff_errors, vec![]);
+
+        let upstream = repo
+            .find_branch("origin/master", git2::BranchType::Remote)?
+            .get()
+            .peel_to_commit()?;
+        let head = repo.head()?.peel_to_commit()?;
+        assert_eq!(head.message(), Some("local.txt"));
+        assert_eq!(head.parent_id(0)?, upstream.id());
+        assert!(repo.workdir().unwrap().join("local.txt").exists());
+        assert!(repo.statuses(None)?.is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn ff_rebase_aborts_on_conflicts() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let project_path = "new_commit/diverged";
+        let repo = Repository::open(working_dir.join(project_path))?;
+        set_identity(&repo)?;
+        let base = repo.head()?.peel_to_commit()?.parent_id(0)?;
+        let head_before = commit_file(&repo, "README.md", "Conflicting work", base)?;
+
+        let status_report = rebase_command(project_path).make_report(working_dir, &workspace);
+
+        let (_, status) = status_report.into_iter().next().unwrap();
+        let report = status.unwrap();
+        let status = report.fetch.status;
+        assert!(status.iter().all(|branch| !branch.rebased));
+        assert_eq!(
+            report
+                .ff_errors
+                .iter()
+                .map(|e| e.branch.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["master"]
+        );
+        assert!(report.ff_errors[0].message.starts_with("Conflicts"));
+        assert_eq!(repo.head()?.peel_to_commit()?.id(), head_before);
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+        assert!(repo.statuses(None)?.is_empty());
+
+        Ok(())
+    })
+}
@@ -0,0 +1,180 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::path::Path;
+
+use gws::color::palette::Palette;
+use gws::commands::common::exit_codes;
+use gws::commands::common::DirectoryCommand;
+use gws::commands::freeze::Freeze;
+use gws::commands::restore::Restore;
+use gws::commands::restore::RestoreOutcome;
+use gws::config::data::Workspace;
+use gws::data::lockfile;
+use gws::data::lockfile::LockedProject;
+use gws::data::lockfile::Lockfile;
+use gws::data::status::DirtyState;
+
+use util::in_example_workspace;
+use util::Error;
+
+fn freeze(working_dir: &Path, workspace: &Workspace) -> Result<Lockfile, Error> {
+    let lockfile_path = working_dir.join(".projects.lock");
+    let exit_code = Freeze {
+        lockfile: lockfile_path.clone(),
+    }
+    .run(working_dir, workspace, &Palette::default())
+    .expect("Freeze command failed");
+    assert_eq!(exit_code, exit_codes::OK);
+    Ok(lockfile::parse(&std::fs::read_to_string(lockfile_path)?).unwrap())
+}
+
+fn head_commit(repo: &git2::Repository) -> Result<String, Error> {
+    Ok(repo.head()?.peel_to_commit()?.id().to_string())
+}
+
+fn restore_outcomes(
+    working_dir: &Path,
+    workspace: &Workspace,
+    lockfile: &Lockfile,
+) -> Vec<(String, RestoreOutcome)> {
+    Restore {
+        lockfile: working_dir.join(".projects.lock"),
+    }
+    .make_report(working_dir, workspace, lockfile)
+    .into_iter()
+    .map(|(path, outcome)| (path, outcome.unwrap()))
+    .collect()
+}
+
+#[test]
+fn freeze_records_head_of_each_project() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let lockfile = freeze(working_dir, &workspace)?;
+
+        let clean = git2::Repository::open(working_dir.join("clean"))?;
+        assert_eq!(
+            lockfile.projects.get("clean"),
+            Some(&LockedProject {
+                branch: Some("master".to_string()),
+                commit: head_commit(&clean)?,
+            })
+        );
+        assert_eq!(lockfile.projects.len(), workspace.projects.len() - 2);
+        assert!(!lockfile.projects.contains_key("missing_repository"));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn restore_checks_out_recorded_commits() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let lockfile = freeze(working_dir, &workspace)?;
+
+        // Check out another branch
+        let clean = git2::Repository::open(working_dir.join("clean"))?;
+        clean.set_head("refs/heads/feature")?;
+
+        // Detach HEAD at an earlier commit
+        let local = git2::Repository::open(working_dir.join("new_commit/local"))?;
+        let local_head = head_commit(&local)?;
+        let parent = local.head()?.peel_to_commit()?.parent(0)?;
+        local.checkout_tree(parent.as_object(), None)?;
+        local.set_head_detached(parent.id())?;
+
+        // Move the checked out branch
+        let no_upstream = git2::Repository::open(working_dir.join("no_upstream"))?;
+        let no_upstream_head = head_commit(&no_upstream)?;
+        let head = no_upstream.head()?.peel_to_commit()?;
+        let sig = git2::Signature::now("Test", "test@example.org")?;
+        no_upstream.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "Move master for test",
+            &head.tree()?,
+            &[&head],
+        )?;
+
+        let outcomes = restore_outcomes(working_dir, &workspace, &lockfile);
+        let outcome = |path: &str| {
+            outcomes
+                .iter()
+                .find(|(p, _)| p == path)
+                .map(|(_, outcome)| outcome)
+                .unwrap()
+        };
+
+        assert_eq!(outcome("clean"), &RestoreOutcome::CheckedOutBranch);
+        assert_eq!(clean.head()?.shorthand(), Some("master"));
+
+        assert_eq!(
+            outcome("new_commit/local"),
+            &RestoreOutcome::CheckedOutBranch
+        );
+        assert_eq!(local.head()?.shorthand(), Some("master"));
+        assert_eq!(head_commit(&local)?, local_head);
+        assert!(local.statuses(None)?.is_empty());
+
+        assert_eq!(outcome("no_upstream"), &RestoreOutcome::Detached);
+        assert!(no_upstream.head_detached()?);
+        assert_eq!(head_commit(&no_upstream)?, no_upstream_head);
+
+        assert_eq!(
+            outcome("new_commit/remote"),
+            &RestoreOutcome::AlreadyRestored
+        );
+        assert_eq!(outcome("missing_repository"), &RestoreOutcome::Missing);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn restore_refuses_dirty_repositories() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let lockfile = freeze(working_dir, &workspace)?;
+
+        let outcomes = restore_outcomes(working_dir, &workspace, &lockfile);
+
+        assert!(outcomes.contains(&(
+            "changes/changed_files".to_string(),
+            RestoreOutcome::Dirty(DirtyState::UnstagedChanges)
+        )));
+        assert!(outcomes.contains(&(
+            "changes/new_files".to_string(),
+            RestoreOutcome::Dirty(DirtyState::UntrackedFiles)
+        )));
+
+        let exit_code = Restore {
+            lockfile: working_dir.join(".projects.lock"),
+        }
+        .run(working_dir, &workspace, &Palette::default())
+        .expect("Restore command failed");
+        assert_eq!(exit_code, exit_codes::INCONSISTENT_WORKSPACE);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn restore_reports_projects_not_in_workspace_or_lockfile() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let mut lockfile = freeze(working_dir, &workspace)?;
+        let clean = lockfile.projects.remove("clean").unwrap();
+        lockfile.projects.insert("gone".to_string(), clean);
+
+        let outcomes = restore_outcomes(working_dir, &workspace, &lockfile);
+
+        assert!(outcomes.contains(&("clean".to_string(), RestoreOutcome::NotLocked)));
+        assert_eq!(
+            outcomes.last(),
+            Some(&("gone".to_string(), RestoreOutcome::NotInWorkspace))
+        );
+
+        Ok(())
+    })
+}
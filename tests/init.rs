@@ -0,0 +1,136 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::BTreeSet;
+use std::fs::remove_file;
+
+use gws::color::palette::Palette;
+use gws::commands::common::exit_codes;
+use gws::commands::init::Init;
+use gws::config::data::Remote;
+use gws::config::data::Workspace;
+use gws::config::read::read_workspace_file;
+
+use util::in_example_workspace;
+use util::Error;
+
+#[test]
+fn init_lists_existing_repos() -> Result<(), Error> {
+    in_example_workspace(|working_dir, _| {
+        let ws_file_path = working_dir.join(".projects.gws");
+        remove_file(&ws_file_path)?;
+
+        let exit_code = Init {}
+            .run(working_dir, &Palette::default())
+            .expect("Init command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+
+        let workspace: Workspace = read_workspace_file(&ws_file_path).unwrap();
+
+        assert_eq!(
+            workspace
+                .projects
+                .iter()
+                .map(|p| p.path.as_str())
+                .collect::<BTreeSet<&str>>(),
+            vec![
+                "changes/changed_files",
+                "changes/new_files",
+                "clean",
+                "new_commit/diverged",
+                "new_commit/local",
+                "new_commit/remote",
+                "new_commit/unfetched_remote",
+                "no_upstream",
+            ]
+            .into_iter()
+            .collect::<BTreeSet<&str>>()
+        );
+
+        let clean = workspace
+            .projects
+            .iter()
+            .find(|p| p.path == "clean")
+            .unwrap();
+        assert_eq!(clean.main_remote.name, "origin");
+        assert_eq!(
+            clean
+                .extra_remotes
+                .iter()
+                .map(|r| r.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["ahead"]
+        );
+
+        let no_upstream = workspace
+            .projects
+            .iter()
+            .find(|p| p.path == "no_upstream")
+            .unwrap();
+        assert_eq!(no_upstream.extra_remotes, Vec::<Remote>::new());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn init_does_not_overwrite_projects_file() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let exit_code = Init {}
+            .run(working_dir, &Palette::default())
+            .expect("Init command failed");
+        assert_eq!(exit_code, exit_codes::USER_ERROR);
+
+        assert_eq!(
+            read_workspace_file(working_dir.join(".projects.gws")).unwrap(),
+            workspace
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn init_skips_nested_and_hidden_repos() -> Result<(), Error> {
+    in_example_workspace(|working_dir, _| {
+        let ws_file_path = working_dir.join(".projects.gws");
+        remove_file(&ws_file_path)?;
+        for path in &["clean/vendor/nested", ".cache/hidden"] {
+            let repo = git2::Repository::init(working_dir.join(path))?;
+            repo.remote("origin", "https://example.org/nested.git")?;
+        }
+
+        let exit_code = Init {}
+            .run(working_dir, &Palette::default())
+            .expect("Init command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+
+        let workspace: Workspace = read_workspace_file(&ws_file_path).unwrap();
+        assert!(workspace.projects.iter().any(|p| p.path == "clean"));
+        assert!(!workspace
+            .projects
+            .iter()
+            .any(|p| p.path.contains("nested") || p.path.contains("hidden")));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn init_does_not_shadow_toml_projects_file() -> Result<(), Error> {
+    in_example_workspace(|working_dir, _| {
+        let ws_file_path = working_dir.join(".projects.gws");
+        let toml_file_path = working_dir.join(".projects.toml");
+        std::fs::rename(&ws_file_path, &toml_file_path)?;
+
+        let exit_code = Init {}
+            .run(working_dir, &Palette::default())
+            .expect("Init command failed");
+        assert_eq!(exit_code, exit_codes::USER_ERROR);
+        assert!(!ws_file_path.exists());
+
+        Ok(())
+    })
+}
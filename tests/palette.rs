@@ -11,11 +11,15 @@ fn default_palette_is_correct() {
         branch: Colour::Fixed(13).normal(),
         clean: Colour::Fixed(10).normal(),
         cloning: Colour::Fixed(14).normal(),
+        conflict: Colour::Fixed(1).normal(),
         dirty: Colour::Fixed(9).normal(),
         error: Colour::Fixed(9).normal(),
+        in_progress: Colour::Fixed(208).normal(),
         missing: Colour::Fixed(11).normal(),
         repo: Colour::Fixed(12).normal(),
         repo_exists: Colour::Fixed(10).normal(),
+        staged: Colour::Fixed(11).normal(),
+        stashed: Colour::Fixed(8).normal(),
     };
 
     assert_eq!(Palette::default(), expected);
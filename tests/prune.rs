@@ -0,0 +1,283 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::BTreeMap;
+
+use gws::color::palette::Palette;
+use gws::commands::common::exit_codes;
+use gws::commands::common::DirectoryCommand;
+use gws::commands::prune::Prune;
+use gws::commands::prune::PruneBlocker;
+use gws::config::data::Workspace;
+use gws::config::parse::ignore;
+use gws::data::status::DirtyState;
+use gws::data::status::OngoingOperation;
+
+use util::in_example_workspace;
+use util::Error;
+
+const UNLISTED: [&str; 4] = [
+    "changes/new_files",
+    "clean",
+    "new_commit/local",
+    "no_upstream",
+];
+
+fn without_unlisted(workspace: Workspace) -> Workspace {
+    Workspace::from(
+        workspace
+            .projects
+            .into_iter()
+            .filter(|project| !UNLISTED.contains(&project.path.as_str())),
+    )
+}
+
+#[test]
+fn prune_reports_what_prevents_pruning() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let workspace = without_unlisted(workspace);
+        let command = Prune {
+            archive_dir: None,
+            assume_yes: true,
+        };
+
+        let report = command
+            .make_report(working_dir, &workspace)
+            .expect("Prune command failed");
+
+        let expected: BTreeMap<String, Vec<PruneBlocker>> = vec![
+            (
+                "changes/new_files",
+                vec![PruneBlocker::Dirty(DirtyState::UntrackedFiles)],
+            ),
+            ("clean", vec![]),
+            (
+                "new_commit/local",
+                vec![PruneBlocker::UnpushedCommits("master".to_string(), 1)],
+            ),
+            ("no_upstream", vec![]),
+        ]
+        .into_iter()
+        .map(|(path, blockers)| (path.to_string(), blockers))
+        .collect();
+
+        assert_eq!(report, expected);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_blocks_branches_not_on_any_remote() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        git2::Repository::init(working_dir.join("unlisted"))?;
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        let head = repo.head()?.peel_to_commit()?;
+        let tree = head.tree()?;
+        let sig = git2::Signature::now("Test", "test@example.org")?;
+        repo.commit(
+            Some("refs/heads/feature"),
+            &sig,
+            &sig,
+            "Local only",
+            &tree,
+            &[&head],
+        )?;
+
+        let report = Prune {
+            archive_dir: None,
+            assume_yes: true,
+        }
+        .make_report(working_dir, &without_unlisted(workspace))
+        .expect("Prune command failed");
+
+        assert_eq!(
+            report.get("clean"),
+            Some(&vec![PruneBlocker::UnpushedBranch("feature".to_string())])
+        );
+        assert_eq!(report.get("unlisted"), Some(&vec![]));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_deletes_only_safe_repos() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let exit_code = Prune {
+            archive_dir: None,
+            assume_yes: true,
+        }
+        .run(
+            working_dir,
+            &without_unlisted(workspace),
+            &Palette::default(),
+        )
+        .expect("Prune command failed");
+
+        assert_eq!(exit_code, exit_codes::INCONSISTENT_WORKSPACE);
+        assert!(!working_dir.join("clean").exists());
+        assert!(!working_dir.join("no_upstream").exists());
+        assert!(working_dir.join("changes/new_files").exists());
+        assert!(working_dir.join("new_commit/local").exists());
+        assert!(working_dir.join("new_commit/remote").exists());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_moves_repos_to_archive() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let archive_dir = working_dir.join("..").join("archive");
+        let workspace =
+            without_unlisted(workspace).ignoring(ignore::parse("^no_upstream$").unwrap());
+
+        let exit_code = Prune {
+            archive_dir: Some(archive_dir.clone()),
+            assume_yes: true,
+        }
+        .run(working_dir, &workspace, &Palette::default())
+        .expect("Prune command failed");
+
+        assert_eq!(exit_code, exit_codes::INCONSISTENT_WORKSPACE);
+        assert!(!working_dir.join("clean").exists());
+        assert!(archive_dir.join("clean").join(".git").exists());
+        assert!(working_dir.join("no_upstream").exists());
+        assert!(!archive_dir.join("no_upstream").exists());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_refuses_archive_inside_workspace() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let archive_dir = working_dir.join("archive");
+
+        let exit_code = Prune {
+            archive_dir: Some(archive_dir.clone()),
+            assume_yes: true,
+        }
+        .run(
+            working_dir,
+            &without_unlisted(workspace),
+            &Palette::default(),
+        )
+        .expect("Prune command failed");
+
+        assert_eq!(exit_code, exit_codes::USER_ERROR);
+        assert!(working_dir.join("clean").exists());
+        assert!(!archive_dir.exists());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_blocks_dirty_detached_head_with_unpushed_commit() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        let head = repo.head()?.peel_to_commit()?;
+        let sig = git2::Signature::now("Test", "test@example.org")?;
+        let detached = repo.commit(None, &sig, &sig, "Detached", &head.tree()?, &[&head])?;
+        repo.set_head_detached(detached)?;
+
+        std::fs::write(working_dir.join("clean").join("README.md"), "staged")?;
+        let mut index = repo.index()?;
+        index.add_path(std::path::Path::new("README.md"))?;
+        index.write()?;
+        std::fs::write(working_dir.join("clean").join("README.md"), "unstaged")?;
+
+        let workspace = without_unlisted(workspace);
+        let command = Prune {
+            archive_dir: None,
+            assume_yes: true,
+        };
+        assert_eq!(
+            command
+                .make_report(working_dir, &workspace)
+                .expect("Prune command failed")
+                .get("clean"),
+            Some(&vec![
+                PruneBlocker::Dirty(DirtyState::StagedAndUnstagedChanges),
+                PruneBlocker::UnpushedDetachedHead,
+            ])
+        );
+
+        let exit_code = command
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Prune command failed");
+        assert_eq!(exit_code, exit_codes::INCONSISTENT_WORKSPACE);
+        assert!(working_dir.join("clean").exists());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_blocks_operation_and_stashes_on_detached_head() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let mut repo = git2::Repository::open(working_dir.join("clean"))?;
+        let head_id = repo.head()?.peel_to_commit()?.id();
+        repo.set_head_detached(head_id)?;
+
+        let sig = git2::Signature::now("Test", "test@example.org")?;
+        std::fs::write(working_dir.join("clean").join("README.md"), "stashed")?;
+        repo.stash_save(&sig, "stashed", None)?;
+        std::fs::write(repo.path().join("MERGE_HEAD"), format!("{}\n", head_id))?;
+
+        let report = Prune {
+            archive_dir: None,
+            assume_yes: true,
+        }
+        .make_report(working_dir, &without_unlisted(workspace))
+        .expect("Prune command failed");
+
+        assert_eq!(
+            report.get("clean"),
+            Some(&vec![
+                PruneBlocker::Operation(OngoingOperation::Merge),
+                PruneBlocker::Stashes(1),
+            ])
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_blocks_repos_containing_other_repos() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let outer = git2::Repository::init(working_dir.join("new_commit"))?;
+        std::fs::write(outer.path().join("info").join("exclude"), "*\n")?;
+
+        let workspace = without_unlisted(workspace);
+        let command = Prune {
+            archive_dir: None,
+            assume_yes: true,
+        };
+        assert_eq!(
+            command
+                .make_report(working_dir, &workspace)
+                .expect("Prune command failed")
+                .get("new_commit"),
+            Some(&vec![
+                PruneBlocker::ContainsRepository("new_commit/diverged".to_string()),
+                PruneBlocker::ContainsRepository("new_commit/local".to_string()),
+                PruneBlocker::ContainsRepository("new_commit/remote".to_string()),
+                PruneBlocker::ContainsRepository("new_commit/unfetched_remote".to_string()),
+            ])
+        );
+
+        command
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Prune command failed");
+        assert!(working_dir.join("new_commit").join(".git").exists());
+        assert!(working_dir.join("new_commit").join("remote").exists());
+
+        Ok(())
+    })
+}
@@ -0,0 +1,253 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use gws::commands::branch::CreateBranch;
+use gws::commands::common::credentials::Credentials;
+use gws::commands::common::filter::ProjectFilter;
+use gws::commands::common::RepositoryCommand;
+use gws::commands::fetch::Fetch;
+use gws::commands::prune_branches::GoneBranch;
+use gws::commands::prune_branches::GoneBranchOutcome;
+use gws::commands::prune_branches::PruneBranches;
+use gws::commands::status::Status;
+use gws::config::data::Workspace;
+
+use util::in_example_workspace;
+use util::Error;
+
+fn clean_only() -> ProjectFilter {
+    ProjectFilter::from(
+        vec!["clean".to_string()]
+            .into_iter()
+            .collect::<HashSet<String>>(),
+    )
+}
+
+/// Point the upstream of `branch` in `repo` at a branch that doesn't exist on
+/// `origin`, like after the remote branch was deleted and pruned.
+fn set_gone_upstream(repo: &git2::Repository, branch: &str) -> Result<(), Error> {
+    let mut config = repo.config()?;
+    config.set_str(&format!("branch.{}.remote", branch), "origin")?;
+    config.set_str(
+        &format!("branch.{}.merge", branch),
+        &format!("refs/heads/gone-{}", branch),
+    )?;
+    Ok(())
+}
+
+/// In the `clean` project, give `feature` (merged into `origin/master`) and a
+/// new branch `unmerged` (one commit ahead of `origin/master`) gone upstreams.
+fn make_gone_branches(working_dir: &Path) -> Result<git2::Repository, Error> {
+    let repo = git2::Repository::open(working_dir.join("clean"))?;
+    {
+        let head = repo.head()?.peel_to_commit()?;
+        let sig = git2::Signature::now("Test", "test@example.org")?;
+        repo.commit(
+            Some("refs/heads/unmerged"),
+            &sig,
+            &sig,
+            "Unmerged work",
+            &head.tree()?,
+            &[&head],
+        )?;
+    }
+    set_gone_upstream(&repo, "feature")?;
+    set_gone_upstream(&repo, "unmerged")?;
+    Ok(repo)
+}
+
+fn prune_clean(working_dir: &Path, workspace: &Workspace, dry_run: bool) -> Vec<GoneBranch> {
+    PruneBranches {
+        projects: clean_only(),
+        dry_run,
+    }
+    .make_report(working_dir, workspace)
+    .into_iter()
+    .flat_map(|(_, result)| result.unwrap())
+    .collect()
+}
+
+#[test]
+fn status_flags_branches_with_gone_upstream() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        make_gone_branches(working_dir)?;
+
+        let command = Status {
+            only_changes: true,
+            projects: clean_only(),
+        };
+        let report = command.make_report(working_dir, &workspace);
+        let (_, status) = report.into_iter().next().unwrap();
+        let status = status.unwrap();
+        let feature = status.iter().find(|b| b.name == "feature").unwrap();
+
+        assert_eq!(
+            feature.upstream_name,
+            Some("origin/gone-feature".to_string())
+        );
+        assert_eq!(feature.in_sync, None);
+        assert!(feature.upstream_gone());
+        assert!(!feature.is_clean());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_branches_dry_run_deletes_nothing() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let repo = make_gone_branches(working_dir)?;
+
+        assert_eq!(
+            prune_clean(working_dir, &workspace, true),
+            vec![
+                GoneBranch {
+                    branch: "feature".to_string(),
+                    upstream_name: "origin/gone-feature".to_string(),
+                    outcome: GoneBranchOutcome::WouldDelete,
+                },
+                GoneBranch {
+                    branch: "unmerged".to_string(),
+                    upstream_name: "origin/gone-unmerged".to_string(),
+                    outcome: GoneBranchOutcome::NotMerged,
+                },
+            ]
+        );
+        assert!(repo.find_branch("feature", git2::BranchType::Local).is_ok());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_branches_deletes_only_merged_branches() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let repo = make_gone_branches(working_dir)?;
+        set_gone_upstream(&repo, "master")?;
+
+        assert_eq!(
+            prune_clean(working_dir, &workspace, false)
+                .into_iter()
+                .map(|gone| (gone.branch, gone.outcome))
+                .collect::<Vec<(String, GoneBranchOutcome)>>(),
+            vec![
+                ("feature".to_string(), GoneBranchOutcome::Deleted),
+                ("master".to_string(), GoneBranchOutcome::CheckedOut),
+                ("unmerged".to_string(), GoneBranchOutcome::NotMerged),
+            ]
+        );
+        assert!(repo
+            .find_branch("feature", git2::BranchType::Local)
+            .is_err());
+        assert!(repo.find_branch("master", git2::BranchType::Local).is_ok());
+        assert!(repo
+            .find_branch("unmerged", git2::BranchType::Local)
+            .is_ok());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_branches_keeps_unpushed_branch_with_set_upstream() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        CreateBranch {
+            status_command: Status {
+                only_changes: false,
+                projects: clean_only(),
+            },
+            name: "topic".to_string(),
+            from_default: false,
+            upstream_remote: Some("origin".to_string()),
+        }
+        .make_report(working_dir, &workspace);
+
+        let status = Status {
+            only_changes: false,
+            projects: clean_only(),
+        }
+        .make_report(working_dir, &workspace);
+        let topic_gone: Vec<bool> = status
+            .into_values()
+            .flat_map(|status| status.unwrap().into_iter())
+            .filter(|branch| branch.name == "topic")
+            .map(|branch| branch.upstream_gone())
+            .collect();
+        assert_eq!(topic_gone, vec![false]);
+
+        assert_eq!(prune_clean(working_dir, &workspace, false), vec![]);
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        assert!(repo.find_branch("topic", git2::BranchType::Local).is_ok());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prune_branches_deletes_branches_merged_into_former_upstream() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        // stacked was merged into origin/base, which was then deleted on the
+        // remote without being merged into master
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        {
+            let sig = git2::Signature::now("Test", "test@example.org")?;
+            let mut parent = repo.head()?.peel_to_commit()?;
+            for name in &["stacked", "base", "after_base"] {
+                let oid = repo.commit(
+                    Some(&format!("refs/heads/{}", name)),
+                    &sig,
+                    &sig,
+                    name,
+                    &parent.tree()?,
+                    &[&parent],
+                )?;
+                parent = repo.find_commit(oid)?;
+            }
+            let base = repo.find_branch("base", git2::BranchType::Local)?;
+            let base_target = base.get().target().unwrap();
+            base.into_reference().delete()?;
+            repo.reference("refs/remotes/origin/base", base_target, false, "test")?;
+            for name in &["stacked", "after_base"] {
+                repo.find_branch(name, git2::BranchType::Local)?
+                    .set_upstream(Some("origin/base"))?;
+            }
+        }
+
+        Fetch {
+            status_command: Status {
+                only_changes: false,
+                projects: clean_only(),
+            },
+            jobs: 1,
+            credentials: Credentials::default(),
+            prune: Some(true),
+            tags: None,
+            remote: Some("origin".to_string()),
+        }
+        .make_report(working_dir, &workspace);
+        assert!(repo
+            .find_branch("origin/base", git2::BranchType::Remote)
+            .is_err());
+
+        assert_eq!(
+            prune_clean(working_dir, &workspace, false)
+                .into_iter()
+                .map(|gone| (gone.branch, gone.outcome))
+                .collect::<Vec<(String, GoneBranchOutcome)>>(),
+            vec![
+                ("after_base".to_string(), GoneBranchOutcome::NotMerged),
+                ("stacked".to_string(), GoneBranchOutcome::Deleted),
+            ]
+        );
+        assert!(repo
+            .find_branch("stacked", git2::BranchType::Local)
+            .is_err());
+
+        Ok(())
+    })
+}
@@ -0,0 +1,207 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::HashSet;
+
+use gws::color::palette::Palette;
+use gws::commands::common::credentials::Credentials;
+use gws::commands::common::exit_codes;
+use gws::commands::common::filter::ProjectFilter;
+use gws::commands::common::RepositoryCommand;
+use gws::commands::push::Push;
+use gws::commands::push::PushReport;
+use gws::commands::status::Status;
+use gws::config::data::Workspace;
+use gws::data::status::PushOutcome;
+use gws::data::status::PushResult;
+
+use util::in_example_workspace;
+use util::Error;
+
+fn make_command(paths: &[&str], dry_run: bool, only_head: bool) -> Push {
+    Push {
+        status_command: Status {
+            only_changes: false,
+            projects: ProjectFilter::from(
+                paths
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect::<HashSet<String>>(),
+            ),
+        },
+        jobs: 1,
+        credentials: Credentials::default(),
+        dry_run,
+        only_head,
+    }
+}
+
+fn report(command: &Push, working_dir: &std::path::Path, workspace: &Workspace) -> Vec<PushReport> {
+    command
+        .make_report(working_dir, workspace)
+        .into_values()
+        .map(|status| status.expect("Failed to compute status"))
+        .collect()
+}
+
+fn upstream_head(
+    working_dir: &std::path::Path,
+    project: &str,
+    branch: &str,
+) -> Result<git2::Oid, Error> {
+    let repo = git2::Repository::open(working_dir.join(project))?;
+    let remote_url = repo.find_remote("origin")?.url().unwrap().to_string();
+    let origin = git2::Repository::open(remote_url)?;
+    let oid = origin
+        .find_branch(branch, git2::BranchType::Local)?
+        .get()
+        .peel_to_commit()?
+        .id();
+    Ok(oid)
+}
+
+/// Point the `origin` remote of `project` at a bare clone of itself, since
+/// libgit2 can't push to non-bare local repositories.
+fn use_bare_origin(working_dir: &std::path::Path, project: &str) -> Result<(), Error> {
+    let repo = git2::Repository::open(working_dir.join(project))?;
+    let origin_url = repo.find_remote("origin")?.url().unwrap().to_string();
+    let bare_path = working_dir.join("..").join("bare_origin");
+    git2::build::RepoBuilder::new()
+        .bare(true)
+        .clone(&origin_url, &bare_path)?;
+    repo.remote_set_url("origin", bare_path.to_str().unwrap())?;
+    Ok(())
+}
+
+#[test]
+fn push_updates_upstream_of_branches_ahead() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        use_bare_origin(working_dir, "new_commit/local")?;
+        let repo = git2::Repository::open(working_dir.join("new_commit/local"))?;
+        let local_head = repo.head()?.peel_to_commit()?.id();
+        assert_ne!(
+            upstream_head(working_dir, "new_commit/local", "master")?,
+            local_head
+        );
+
+        let statuses = report(
+            &make_command(&["new_commit/local"], false, false),
+            working_dir,
+            &workspace,
+        );
+
+        assert_eq!(
+            statuses[0].push_results,
+            vec![PushResult {
+                branch: "master".to_string(),
+                upstream_name: "origin/master".to_string(),
+                commits: 1,
+                outcome: PushOutcome::Pushed,
+            }]
+        );
+        assert!(statuses[0].status.iter().all(|b| b.in_sync == Some(true)));
+        assert_eq!(
+            upstream_head(working_dir, "new_commit/local", "master")?,
+            local_head
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn push_failures_are_reported() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let command = make_command(&["new_commit/local"], false, false);
+
+        let statuses = report(&command, working_dir, &workspace);
+        assert_eq!(statuses[0].push_results.len(), 1);
+        assert!(matches!(
+            statuses[0].push_results[0].outcome,
+            PushOutcome::Failed(_)
+        ));
+
+        let exit_code = command
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Push command failed");
+        assert_eq!(exit_code, exit_codes::INTERNAL_ERROR);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn dry_run_pushes_nothing() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let upstream_before = upstream_head(working_dir, "new_commit/local", "master")?;
+
+        let statuses = report(
+            &make_command(&["new_commit/local"], true, false),
+            working_dir,
+            &workspace,
+        );
+
+        assert_eq!(statuses[0].push_results.len(), 1);
+        assert_eq!(statuses[0].push_results[0].outcome, PushOutcome::WouldPush);
+        assert_eq!(
+            upstream_head(working_dir, "new_commit/local", "master")?,
+            upstream_before
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn push_skips_diverged_and_behind_branches() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let command = make_command(
+            &[
+                "new_commit/diverged",
+                "new_commit/remote",
+                "clean",
+                "no_upstream",
+            ],
+            false,
+            false,
+        );
+
+        for status in report(&command, working_dir, &workspace) {
+            assert_eq!(status.push_results, vec![]);
+        }
+
+        let exit_code = command
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Push command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn only_head_skips_other_branches() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        let repo = git2::Repository::open(working_dir.join("new_commit/local"))?;
+        repo.set_head("refs/heads/master2")?;
+
+        let statuses = report(
+            &make_command(&["new_commit/local"], true, true),
+            working_dir,
+            &workspace,
+        );
+        assert_eq!(statuses[0].push_results, vec![]);
+
+        let statuses = report(
+            &make_command(&["new_commit/local"], true, false),
+            working_dir,
+            &workspace,
+        );
+        assert_eq!(statuses[0].push_results.len(), 1);
+        assert_eq!(statuses[0].push_results[0].branch, "master");
+
+        Ok(())
+    })
+}
@@ -0,0 +1,129 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::HashSet;
+
+use gws::color::palette::Palette;
+use gws::commands::common::exit_codes;
+use gws::commands::common::filter::ProjectFilter;
+use gws::commands::common::DirectoryCommand;
+use gws::commands::remotes::RemoteChange;
+use gws::commands::remotes::SyncRemotes;
+use gws::config::data::Project;
+use gws::config::data::Workspace;
+
+use util::in_example_workspace;
+use util::Error;
+
+/// Remove the `ahead` remote, change the URL of `origin` and add an unlisted
+/// `fork` remote in the `clean` project.
+fn unsync_clean_remotes(working_dir: &std::path::Path) -> Result<(), Error> {
+    let repo = git2::Repository::open(working_dir.join("clean"))?;
+    repo.remote_delete("ahead")?;
+    repo.remote_set_url("origin", "https://example.org/old.git")?;
+    repo.remote("fork", "https://example.org/fork.git")?;
+    Ok(())
+}
+
+fn clean_project(workspace: &Workspace) -> &Project {
+    workspace
+        .projects
+        .iter()
+        .find(|p| p.path == "clean")
+        .unwrap()
+}
+
+fn sync_clean(dry_run: bool) -> SyncRemotes {
+    SyncRemotes {
+        projects: ProjectFilter::from(
+            vec!["clean".to_string()]
+                .into_iter()
+                .collect::<HashSet<String>>(),
+        ),
+        dry_run,
+    }
+}
+
+#[test]
+fn sync_reports_added_changed_and_unlisted_remotes() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        unsync_clean_remotes(working_dir)?;
+        let project = clean_project(&workspace);
+
+        let report = sync_clean(true)
+            .make_report(working_dir, &workspace)
+            .expect("Sync command failed");
+
+        assert_eq!(
+            report.get("clean"),
+            Some(&vec![
+                RemoteChange::SetUrl {
+                    remote: project.main_remote.clone(),
+                    old_url: "https://example.org/old.git".to_string(),
+                },
+                RemoteChange::Add(project.extra_remotes[0].clone()),
+                RemoteChange::Unlisted("fork".to_string()),
+            ])
+        );
+        assert_eq!(report.len(), 1);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn dry_run_changes_nothing() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        unsync_clean_remotes(working_dir)?;
+
+        let exit_code = sync_clean(true)
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Sync command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        assert_eq!(
+            repo.find_remote("origin")?.url(),
+            Some("https://example.org/old.git")
+        );
+        assert!(repo.find_remote("ahead").is_err());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn sync_updates_remotes_and_keeps_unlisted_ones() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace: Workspace| {
+        unsync_clean_remotes(working_dir)?;
+        let project = clean_project(&workspace);
+
+        let exit_code = sync_clean(false)
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Sync command failed");
+        assert_eq!(exit_code, exit_codes::OK);
+
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        assert_eq!(
+            repo.find_remote("origin")?.url(),
+            Some(project.main_remote.url.as_str())
+        );
+        assert_eq!(
+            repo.find_remote("ahead")?.url(),
+            Some(project.extra_remotes[0].url.as_str())
+        );
+        assert!(repo.find_remote("fork").is_ok());
+
+        let report = sync_clean(true)
+            .make_report(working_dir, &workspace)
+            .expect("Sync command failed");
+        assert_eq!(
+            report.get("clean"),
+            Some(&vec![RemoteChange::Unlisted("fork".to_string())])
+        );
+
+        Ok(())
+    })
+}
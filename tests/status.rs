@@ -3,24 +3,33 @@ extern crate gws;
 
 mod util;
 
-use std::collections::BTreeSet;
 use std::collections::HashSet;
+use std::fs::write;
+use std::path::Path;
 
+use gws::commands::common::filter::ProjectFilter;
 use gws::commands::common::RepositoryCommand;
 use gws::commands::error::Error;
 use gws::commands::status::Status;
+use gws::config::data::Workspace;
 use gws::data::status::BranchStatus;
+use gws::data::status::DetachedHead;
 use gws::data::status::DirtyState;
+use gws::data::status::Head;
+use gws::data::status::OngoingOperation;
 use gws::data::status::RepositoryStatus;
 
 use util::in_example_workspace;
 
-pub fn tree_set<I, T>(items: I) -> BTreeSet<T>
+/// The status of a repository with `master` checked out.
+pub fn repository_status<I>(branches: I) -> RepositoryStatus
 where
-    I: IntoIterator<Item = T>,
-    T: Ord,
+    I: IntoIterator<Item = BranchStatus>,
 {
-    items.into_iter().collect()
+    RepositoryStatus {
+        branches: branches.into_iter().collect(),
+        head: Head::Branch("master".to_string()),
+    }
 }
 
 #[test]
@@ -28,7 +37,7 @@ fn status_produces_correct_data_structure() -> Result<(), util::Error> {
     in_example_workspace(|working_dir, workspace| {
         let command = Status {
             only_changes: false,
-            projects: HashSet::new(),
+            projects: ProjectFilter::default(),
         };
 
         let project_stati: Vec<Result<RepositoryStatus, Error>> = command
@@ -41,66 +50,96 @@ fn status_produces_correct_data_structure() -> Result<(), util::Error> {
             project_stati,
             vec![
                 // changes/changed_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
-                        dirty: DirtyState::UncommittedChanges,
+                        dirty: DirtyState::UnstagedChanges,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // changes/new_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::UntrackedFiles,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // clean
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "feature".to_string(),
                         upstream_name: None,
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: None,
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // missing_repository
@@ -108,96 +147,141 @@ fn status_produces_correct_data_structure() -> Result<(), util::Error> {
                 // missing_repository_2
                 Err(Error::RepositoryMissing),
                 // new_commit/diverged
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: Some("origin/master".to_string()),
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: Some(false),
+                    ahead: 1,
+                    behind: 1,
                     upstream_fetched: false,
                     fast_forwarded: false,
+                    rebased: false,
                 },])),
                 // new_commit/local
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(false),
+                        ahead: 1,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/remote
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(false),
+                        ahead: 0,
+                        behind: 1,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(false),
+                        ahead: 0,
+                        behind: 1,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "merginator".to_string(),
                         upstream_name: Some("ahead/merginator".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(false),
+                        ahead: 0,
+                        behind: 4,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/unfetched_remote
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // no_upstream
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: None,
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: None,
+                    ahead: 0,
+                    behind: 0,
                     upstream_fetched: false,
                     fast_forwarded: false,
+                    rebased: false,
                 },])),
             ]
         );
@@ -211,7 +295,7 @@ fn status_ignores_clean_repos_with_only_changes() -> Result<(), util::Error> {
     in_example_workspace(|working_dir, workspace| {
         let command = Status {
             only_changes: true,
-            projects: HashSet::new(),
+            projects: ProjectFilter::default(),
         };
 
         let project_stati: Vec<Result<RepositoryStatus, Error>> = command
@@ -224,117 +308,172 @@ fn status_ignores_clean_repos_with_only_changes() -> Result<(), util::Error> {
             project_stati,
             vec![
                 // changes/changed_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
-                        dirty: DirtyState::UncommittedChanges,
+                        dirty: DirtyState::UnstagedChanges,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // changes/new_files
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::UntrackedFiles,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/diverged
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: Some("origin/master".to_string()),
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: Some(false),
+                    ahead: 1,
+                    behind: 1,
                     upstream_fetched: false,
                     fast_forwarded: false,
+                    rebased: false,
                 },])),
                 // new_commit/local
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("origin/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(false),
+                        ahead: 1,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(true),
+                        ahead: 0,
+                        behind: 0,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // new_commit/remote
-                Ok(tree_set(vec![
+                Ok(repository_status(vec![
                     BranchStatus {
                         name: "master".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: true,
                         in_sync: Some(false),
+                        ahead: 0,
+                        behind: 1,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "master2".to_string(),
                         upstream_name: Some("ahead/master".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(false),
+                        ahead: 0,
+                        behind: 1,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     },
                     BranchStatus {
                         name: "merginator".to_string(),
                         upstream_name: Some("ahead/merginator".to_string()),
                         dirty: DirtyState::Clean,
+                        operation: None,
+                        stashes: 0,
                         is_head: false,
                         in_sync: Some(false),
+                        ahead: 0,
+                        behind: 4,
                         upstream_fetched: false,
                         fast_forwarded: false,
+                        rebased: false,
                     }
                 ])),
                 // no_upstream
-                Ok(tree_set(vec![BranchStatus {
+                Ok(repository_status(vec![BranchStatus {
                     name: "master".to_string(),
                     upstream_name: None,
                     dirty: DirtyState::Clean,
+                    operation: None,
+                    stashes: 0,
                     is_head: true,
                     in_sync: None,
+                    ahead: 0,
+                    behind: 0,
                     upstream_fetched: false,
                     fast_forwarded: false,
+                    rebased: false,
                 },])),
             ]
         );
@@ -342,3 +481,137 @@ fn status_ignores_clean_repos_with_only_changes() -> Result<(), util::Error> {
         Ok(())
     })
 }
+
+fn clean_project_head_status(working_dir: &Path, workspace: &Workspace) -> BranchStatus {
+    let command = Status {
+        only_changes: false,
+        projects: ProjectFilter::from(
+            vec!["clean".to_string()]
+                .into_iter()
+                .collect::<HashSet<String>>(),
+        ),
+    };
+
+    command
+        .make_report(working_dir, workspace)
+        .into_values()
+        .flat_map(|s| s.unwrap())
+        .find(|b| b.is_head)
+        .unwrap()
+}
+
+#[test]
+fn status_distinguishes_staged_and_unstaged_changes() -> Result<(), util::Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+
+        write(working_dir.join("clean").join("staged.txt"), "foo")?;
+        let mut index = repo.index()?;
+        index.add_path(Path::new("staged.txt"))?;
+        index.write()?;
+        assert_eq!(
+            clean_project_head_status(working_dir, &workspace).dirty,
+            DirtyState::StagedChanges
+        );
+
+        write(working_dir.join("clean").join("README.md"), "bar")?;
+        assert_eq!(
+            clean_project_head_status(working_dir, &workspace).dirty,
+            DirtyState::StagedAndUnstagedChanges
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn status_counts_stashes() -> Result<(), util::Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let mut repo = git2::Repository::open(working_dir.join("clean"))?;
+        let sig = git2::Signature::now("Test", "test@example.org")?;
+
+        for content in &["foo", "bar"] {
+            write(working_dir.join("clean").join("README.md"), content)?;
+            repo.stash_save(&sig, content, None)?;
+        }
+
+        let status = clean_project_head_status(working_dir, &workspace);
+        assert_eq!(status.dirty, DirtyState::Clean);
+        assert_eq!(status.stashes, 2);
+        assert!(status.is_clean());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn status_reports_ongoing_merge() -> Result<(), util::Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        let head_id = repo.head()?.peel_to_commit()?.id();
+        write(repo.path().join("MERGE_HEAD"), format!("{}\n", head_id))?;
+
+        let status = clean_project_head_status(working_dir, &workspace);
+        assert_eq!(status.operation, Some(OngoingOperation::Merge));
+        assert!(!status.is_clean());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn status_reports_conflicted_rebase_on_detached_head() -> Result<(), util::Error> {
+    in_example_workspace(|working_dir, workspace| {
+        let repo = git2::Repository::open(working_dir.join("clean"))?;
+        let sig = git2::Signature::now("Test", "test@example.org")?;
+        let base = repo.head()?.peel_to_commit()?;
+
+        let commit_readme = |update_ref: &str, content: &str| -> Result<git2::Oid, util::Error> {
+            let mut tree = repo.treebuilder(Some(&base.tree()?))?;
+            tree.insert("README.md", repo.blob(content.as_bytes())?, 0o100644)?;
+            let tree = repo.find_tree(tree.write()?)?;
+            Ok(repo.commit(Some(update_ref), &sig, &sig, content, &tree, &[&base])?)
+        };
+        let local = commit_readme("refs/heads/master", "local")?;
+        let onto = commit_readme("refs/heads/other", "other")?;
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+
+        let mut rebase = repo.rebase(
+            Some(&repo.find_annotated_commit(local)?),
+            Some(&repo.find_annotated_commit(onto)?),
+            None,
+            None,
+        )?;
+        rebase.next().unwrap()?;
+        assert!(repo.index()?.has_conflicts());
+
+        let command = Status {
+            only_changes: true,
+            projects: ProjectFilter::from(
+                vec!["clean".to_string()]
+                    .into_iter()
+                    .collect::<HashSet<String>>(),
+            ),
+        };
+        let status = command
+            .make_report(working_dir, &workspace)
+            .into_values()
+            .next()
+            .expect("Rebasing project should be reported with --only-changes")
+            .unwrap();
+
+        assert_eq!(
+            status.head,
+            Head::Detached(DetachedHead {
+                commit: onto.to_string(),
+                dirty: DirtyState::Conflicts,
+                operation: Some(OngoingOperation::Rebase),
+                stashes: 0,
+            })
+        );
+        assert!(!status.head.is_clean());
+        assert_eq!(status.iter().filter(|b| b.is_head).count(), 0);
+
+        Ok(())
+    })
+}
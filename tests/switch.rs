@@ -0,0 +1,137 @@
+extern crate git2;
+extern crate gws;
+
+mod util;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use gws::color::palette::Palette;
+use gws::commands::common::exit_codes;
+use gws::commands::common::filter::ProjectFilter;
+use gws::commands::common::DirectoryCommand;
+use gws::commands::switch::Switch;
+use gws::commands::switch::SwitchOutcome;
+use gws::config::data::Workspace;
+use gws::data::status::DirtyState;
+
+use util::in_example_workspace;
+use util::Error;
+
+fn switch(branch: &str, projects: &[&str]) -> Switch {
+    Switch {
+        projects: ProjectFilter::from(
+            projects
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<HashSet<String>>(),
+        ),
+        branch: branch.to_string(),
+    }
+}
+
+fn outcomes(
+    command: &Switch,
+    working_dir: &Path,
+    workspace: &Workspace,
+) -> Vec<(String, SwitchOutcome)> {
+    command
+        .make_report_and_maybe_print(working_dir, workspace, None)
+        .into_iter()
+        .map(|(project, outcome)| (project.path.clone(), outcome.unwrap()))
+        .collect()
+}
+
+fn head_name(working_dir: &Path, project: &str) -> Result<Option<String>, Error> {
+    let repo = git2::Repository::open(working_dir.join(project))?;
+    let head = repo.head()?;
+    Ok(head.shorthand().map(str::to_string))
+}
+
+/// Give `project` a remote-tracking branch `origin/feature` but no local
+/// `feature` branch.
+fn add_remote_feature_branch(working_dir: &Path, project: &str) -> Result<(), Error> {
+    let repo = git2::Repository::open(working_dir.join(project))?;
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reference(
+        "refs/remotes/origin/feature",
+        head.id(),
+        false,
+        "Add remote branch for test",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn switch_checks_out_local_or_remote_branches() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        add_remote_feature_branch(working_dir, "new_commit/remote")?;
+
+        let command = switch("feature", &["clean", "new_commit/remote", "no_upstream"]);
+        assert_eq!(
+            outcomes(&command, working_dir, &workspace),
+            vec![
+                ("clean".to_string(), SwitchOutcome::CheckedOut),
+                (
+                    "new_commit/remote".to_string(),
+                    SwitchOutcome::CreatedTracking("origin/feature".to_string())
+                ),
+                ("no_upstream".to_string(), SwitchOutcome::NoSuchBranch),
+            ]
+        );
+
+        assert_eq!(
+            head_name(working_dir, "clean")?,
+            Some("feature".to_string())
+        );
+        assert_eq!(
+            head_name(working_dir, "new_commit/remote")?,
+            Some("feature".to_string())
+        );
+        assert_eq!(
+            head_name(working_dir, "no_upstream")?,
+            Some("master".to_string())
+        );
+
+        let repo = git2::Repository::open(working_dir.join("new_commit/remote"))?;
+        let feature = repo.find_branch("feature", git2::BranchType::Local)?;
+        assert_eq!(feature.upstream()?.name()?, Some("origin/feature"));
+
+        assert_eq!(
+            outcomes(&switch("feature", &["clean"]), working_dir, &workspace),
+            vec![("clean".to_string(), SwitchOutcome::AlreadyOnBranch)]
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn switch_refuses_dirty_repositories() -> Result<(), Error> {
+    in_example_workspace(|working_dir, workspace| {
+        add_remote_feature_branch(working_dir, "changes/changed_files")?;
+
+        let command = switch("feature", &["changes/changed_files", "clean"]);
+        assert_eq!(
+            outcomes(&command, working_dir, &workspace),
+            vec![
+                (
+                    "changes/changed_files".to_string(),
+                    SwitchOutcome::Dirty(DirtyState::UnstagedChanges)
+                ),
+                ("clean".to_string(), SwitchOutcome::CheckedOut),
+            ]
+        );
+        assert_eq!(
+            head_name(working_dir, "changes/changed_files")?,
+            Some("master".to_string())
+        );
+
+        let exit_code = command
+            .run(working_dir, &workspace, &Palette::default())
+            .expect("Switch command failed");
+        assert_eq!(exit_code, exit_codes::INCONSISTENT_WORKSPACE);
+
+        Ok(())
+    })
+}
@@ -10,6 +10,8 @@ use std::hash::Hash;
 use git2::Repository;
 
 use gws::color::palette::Palette;
+use gws::commands::common::credentials::Credentials;
+use gws::commands::common::filter::ProjectFilter;
 use gws::commands::common::DirectoryCommand;
 use gws::commands::update::Update;
 use gws::config::data::Workspace;
@@ -30,7 +32,11 @@ where
 #[test]
 fn update_creates_repos() -> Result<(), Error> {
     in_example_workspace(|working_dir, workspace: Workspace| {
-        let command: Update = Update {};
+        let command: Update = Update {
+            projects: ProjectFilter::default(),
+            jobs: 1,
+            credentials: Credentials::default(),
+        };
 
         assert_eq!(false, working_dir.join("missing_repository").exists());
         assert_eq!(false, working_dir.join("missing_repository_2").exists());
@@ -49,7 +55,11 @@ fn update_works_with_public_https() -> Result<(), Error> {
     let projects_contents = "gws2 | https://github.com/emlun/gws2.git";
 
     in_workspace_with_projects_file(projects_contents, |working_dir, workspace: Workspace| {
-        let command: Update = Update {};
+        let command: Update = Update {
+            projects: ProjectFilter::default(),
+            jobs: 1,
+            credentials: Credentials::default(),
+        };
 
         let repo_path: String = workspace
             .projects
@@ -85,7 +95,11 @@ fn update_works_with_ssh() -> Result<(), Error> {
     in_workspace_with_projects_file(
         projects_contents,
         with_bundled_ssh_key_in_agent(|working_dir, workspace: Workspace| {
-            let command: Update = Update {};
+            let command: Update = Update {
+                projects: ProjectFilter::default(),
+                jobs: 1,
+                credentials: Credentials::default(),
+            };
 
             let repo_path: String = workspace
                 .projects